@@ -134,17 +134,17 @@ pub mod p30;
 pub mod p7;
 // pub mod p10;
 // pub mod p11;
-// pub mod p12;
-// pub mod p13;
-// pub mod p14;
-// pub mod p15;
-// pub mod p16;
-// pub mod p18;
+pub mod p12;
+pub mod p13;
+pub mod p14;
+pub mod p15;
+pub mod p16;
+pub mod p18;
 pub mod p19;
-// pub mod p2;
+pub mod p2;
 pub mod p21;
-// pub mod p22;
-// pub mod p23;
-// pub mod p26;
+pub mod p22;
+pub mod p23;
+pub mod p26;
 // pub mod p27;
 // pub mod p29;
@@ -19,22 +19,202 @@ use num_enum::IntoPrimitive;
 const MAIN_CONTROL: u8 = 0x00;
 const GENERAL_STATUS: u8 = 0x02;
 const SENSOR_INPUT_STATUS: u8 = 0x03;
+const NOISE_FLAG_STATUS: u8 = 0x0A;
 const SENSOR_INPUT_1_DELTA_COUNT: u8 = 0x10;
 const SENSOR_INPUT_2_DELTA_COUNT: u8 = 0x11;
 const SENSOR_INPUT_3_DELTA_COUNT: u8 = 0x12;
 const SENSITIVITY_CONTROL: u8 = 0x1F;
+const SENSOR_INPUT_CONFIG: u8 = 0x22;
+const SENSOR_INPUT_CONFIG_2: u8 = 0x23;
+const AVERAGING_AND_SAMPLING_CONFIG: u8 = 0x24;
+const CALIBRATION_ACTIVATE: u8 = 0x26;
+const INTERRUPT_ENABLE: u8 = 0x27;
+const REPEAT_RATE_ENABLE: u8 = 0x28;
 const MULTIPLE_TOUCH_CONFIG: u8 = 0x2A;
+const SENSOR_INPUT_1_THRESHOLD: u8 = 0x30;
+const STANDBY_CHANNEL: u8 = 0x40;
+const STANDBY_THRESHOLD: u8 = 0x43;
+const CONFIGURATION_2: u8 = 0x44;
+const SENSOR_INPUT_1_BASE_COUNT: u8 = 0x50;
 
-#[derive(IntoPrimitive)]
-#[repr(u8)]
+/// Whether more than one pad may report a touch at the same time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TouchMode {
-    Single = 0xFF,
-    Multi = 0x7F,
+    /// Multiple touch blocking is enabled and only a single touch is reported.
+    Single,
+    /// Multiple touch blocking is disabled so every touched pad is reported.
+    Multi,
+}
+
+/// One of the three touch pads, numbered as printed on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pad {
+    One,
+    Two,
+    Three,
+}
+
+impl Pad {
+    const ALL: [Self; 3] = [Self::One, Self::Two, Self::Three];
+
+    /// Zero based, also the offset of the pad's per input registers.
+    const fn index(self) -> u8 {
+        match self {
+            Self::One => 0,
+            Self::Two => 1,
+            Self::Three => 2,
+        }
+    }
+}
+
+/// The direction of a slide gesture across all three pads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlideDirection {
+    /// Pad 1 to pad 3.
+    Forward,
+    /// Pad 3 to pad 1.
+    Backward,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Touch(Pad),
+    Release(Pad),
+    Slide(SlideDirection),
+}
+
+/// The events produced by a single call to [`P12::poll`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Events {
+    buffer: [Option<Event>; 7],
+    len: usize,
+    next: usize,
+}
+
+impl Events {
+    const fn new() -> Self {
+        Self {
+            buffer: [None; 7],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    const fn push(&mut self, event: Event) {
+        self.buffer[self.len] = Some(event);
+        self.len += 1;
+    }
+}
+
+impl Iterator for Events {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if self.next < self.len {
+            self.next += 1;
+            self.buffer[self.next - 1]
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Tracker {
+    touched: [bool; 3],
+    last: Option<usize>,
+    direction: Option<SlideDirection>,
+}
+
+impl Tracker {
+    fn update(&mut self, pads: (bool, bool, bool)) -> Events {
+        let touched = [pads.0, pads.1, pads.2];
+        let mut events = Events::new();
+        for pad in Pad::ALL {
+            let index = usize::from(pad.index());
+            if self.touched[index] && !touched[index] {
+                events.push(Event::Release(pad));
+            }
+        }
+        for pad in Pad::ALL {
+            let index = usize::from(pad.index());
+            if !self.touched[index] && touched[index] {
+                events.push(Event::Touch(pad));
+                if let Some(direction) = self.step(index) {
+                    events.push(Event::Slide(direction));
+                }
+            }
+        }
+        self.touched = touched;
+        events
+    }
+
+    // Two consecutive steps between neighbouring pads in the same direction cover all three pads.
+    fn step(&mut self, index: usize) -> Option<SlideDirection> {
+        let direction = match self.last {
+            Some(last) if index == last + 1 => Some(SlideDirection::Forward),
+            Some(last) if index + 1 == last => Some(SlideDirection::Backward),
+            _ => None,
+        };
+        if direction.is_some() && direction == self.direction {
+            self.last = None;
+            self.direction = None;
+            direction
+        } else {
+            self.last = Some(index);
+            self.direction = direction;
+            None
+        }
+    }
+}
+
+/// The number of samples averaged for each measurement.
+#[derive(IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Averaging {
+    Samples1 = 0b0000_0000,
+    Samples2 = 0b0001_0000,
+    Samples4 = 0b0010_0000,
+    Samples8 = 0b0011_0000,
+    Samples16 = 0b0100_0000,
+    Samples32 = 0b0101_0000,
+    Samples64 = 0b0110_0000,
+    Samples128 = 0b0111_0000,
+}
+
+#[derive(IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SampleTime {
+    Microseconds320 = 0b0000_0000,
+    Microseconds640 = 0b0000_0100,
+    Milliseconds1_28 = 0b0000_1000,
+    Milliseconds2_56 = 0b0000_1100,
+}
+
+/// The interval between measurement cycles of all enabled pads.
+#[derive(IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CycleTime {
+    Milliseconds35 = 0b0000_0000,
+    Milliseconds70 = 0b0000_0001,
+    Milliseconds105 = 0b0000_0010,
+    Milliseconds140 = 0b0000_0011,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerMode {
+    Active,
+    /// Only the pads selected with [`P12::set_standby_channels`] are sampled.
+    Standby,
+    /// No pads are sampled until the device is returned to another mode.
+    DeepSleep,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Error<E> {
     InvalidSensitivity,
+    InvalidDuration,
+    InvalidThreshold,
     I2cError(E),
 }
 
@@ -44,39 +224,56 @@ impl<E> From<E> for Error<E> {
     }
 }
 
+const fn pad_mask(pads: (bool, bool, bool)) -> u8 {
+    (pads.0 as u8) | ((pads.1 as u8) << 1) | ((pads.2 as u8) << 2)
+}
+
+// Repeat rate and press and hold durations are programmed in steps of 35 ms from 35 ms to 560 ms.
+fn duration_step(milliseconds: u16) -> Option<u8> {
+    if milliseconds.is_multiple_of(35) && (35..=560).contains(&milliseconds) {
+        u8::try_from(milliseconds / 35 - 1).ok()
+    } else {
+        None
+    }
+}
+
 pub struct P12<I2C> {
     i2c: I2C,
     address: u8,
+    tracker: Tracker,
 }
 
 impl<I2C: I2c> Driver<I2C, Error<I2C::Error>> for P12<I2C> {
     fn new_inner(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            tracker: Tracker::default(),
+        }
     }
 
     fn init_inner(mut self) -> Result<Self, Error<I2C::Error>> {
-        let touch_mode: Option<TouchMode> = None;
-        let sensitivity: Option<u8> = None;
-        let mut data: [u8; 1] = [0];
-        self.i2c
-            .write_read(self.address, &[MULTIPLE_TOUCH_CONFIG], &mut data)?;
-        // not working
-        self.i2c.write(
-            self.address,
-            &[
-                MULTIPLE_TOUCH_CONFIG,
-                <TouchMode as core::convert::Into<u8>>::into(
-                    touch_mode.unwrap_or(TouchMode::Multi),
-                ) & data[0],
-            ],
-        )?;
-        self.set_sensitivity(sensitivity.unwrap_or(3))?;
+        self.set_touch_mode(TouchMode::Multi)?;
+        self.set_sensitivity(3)?;
 
         Ok(self)
     }
 }
 
 impl<I2C: I2c> P12<I2C> {
+    pub fn set_touch_mode(&mut self, touch_mode: TouchMode) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[MULTIPLE_TOUCH_CONFIG], &mut data)?;
+        let config = match touch_mode {
+            TouchMode::Single => (data[0] & 0b1111_0011) | 0b1000_0000,
+            TouchMode::Multi => data[0] & 0b0111_1111,
+        };
+        self.i2c
+            .write(self.address, &[MULTIPLE_TOUCH_CONFIG, config])?;
+        Ok(())
+    }
+
     pub fn get_sensitivity(&mut self) -> Result<u8, I2C::Error> {
         let mut data: [u8; 1] = [0];
         self.i2c
@@ -101,13 +298,79 @@ impl<I2C: I2c> P12<I2C> {
         Ok(())
     }
 
+    /// Selects which pads assert the ALERT pin when touched.
+    pub fn set_interrupt_enabled(&mut self, pads: (bool, bool, bool)) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[INTERRUPT_ENABLE, pad_mask(pads)])?;
+        Ok(())
+    }
+
+    /// Selects whether releasing a pad also asserts the ALERT pin.
+    pub fn set_interrupt_on_release(&mut self, on_release: bool) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[CONFIGURATION_2], &mut data)?;
+        let config = if on_release {
+            data[0] & 0b1111_1110
+        } else {
+            data[0] | 0b0000_0001
+        };
+        self.i2c.write(self.address, &[CONFIGURATION_2, config])?;
+        Ok(())
+    }
+
+    pub fn interrupt_pending(&mut self) -> Result<bool, I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[MAIN_CONTROL], &mut data)?;
+        Ok((data[0] & 0b0000_0001) != 0x00)
+    }
+
+    /// Selects which pads repeatedly report a touch while held past the press and hold duration.
+    pub fn set_repeat_enabled(&mut self, pads: (bool, bool, bool)) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[REPEAT_RATE_ENABLE, pad_mask(pads)])?;
+        Ok(())
+    }
+
+    /// Sets the interval between repeated touches of a held pad.  `milliseconds` must be a
+    /// multiple of 35 between 35 and 560.
+    pub fn set_repeat_rate(&mut self, milliseconds: u16) -> Result<(), Error<I2C::Error>> {
+        let step = duration_step(milliseconds).ok_or(Error::InvalidDuration)?;
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[SENSOR_INPUT_CONFIG], &mut data)?;
+        self.i2c.write(
+            self.address,
+            &[SENSOR_INPUT_CONFIG, (data[0] & 0xF0) | step],
+        )?;
+        Ok(())
+    }
+
+    /// Sets how long a pad must be held before it is treated as a long press and starts
+    /// repeating.  `milliseconds` must be a multiple of 35 between 35 and 560.
+    pub fn set_long_press_duration(&mut self, milliseconds: u16) -> Result<(), Error<I2C::Error>> {
+        let step = duration_step(milliseconds).ok_or(Error::InvalidDuration)?;
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[SENSOR_INPUT_CONFIG_2], &mut data)?;
+        self.i2c.write(
+            self.address,
+            &[SENSOR_INPUT_CONFIG_2, (data[0] & 0xF0) | step],
+        )?;
+        Ok(())
+    }
+
+    /// Clears the interrupt, releasing the ALERT pin.  Returns the main control register.
+    ///
     /// # Errors
     pub fn clear_interrupt(&mut self) -> Result<u8, I2C::Error> {
         let mut data: [u8; 1] = [0];
-        self.i2c.write(self.address, &[MAIN_CONTROL, 0x00])?;
         self.i2c
             .write_read(self.address, &[MAIN_CONTROL], &mut data)?;
-        Ok(data[0])
+        let control = data[0] & 0b1111_1110;
+        self.i2c.write(self.address, &[MAIN_CONTROL, control])?;
+        Ok(control)
     }
 
     /// # Errors
@@ -125,6 +388,128 @@ impl<I2C: I2c> P12<I2C> {
         ))
     }
 
+    /// Reads the current base counts that touch deltas are measured against.
+    pub fn read_base_counts(&mut self) -> Result<(u8, u8, u8), I2C::Error> {
+        let mut data: [u8; 3] = [0; 3];
+        for (offset, count) in (0_u8..).zip(data.iter_mut()) {
+            let mut register: [u8; 1] = [0];
+            self.i2c.write_read(
+                self.address,
+                &[SENSOR_INPUT_1_BASE_COUNT + offset],
+                &mut register,
+            )?;
+            *count = register[0];
+        }
+        Ok(data.into())
+    }
+
+    /// Forces the selected pads to recalibrate their base counts.  Useful after the environment
+    /// around the pads changes, e.g. a cover being fitted.
+    pub fn calibrate(&mut self, pads: (bool, bool, bool)) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[CALIBRATION_ACTIVATE, pad_mask(pads)])?;
+        Ok(())
+    }
+
+    pub fn is_calibrating(&mut self) -> Result<bool, I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[CALIBRATION_ACTIVATE], &mut data)?;
+        Ok((data[0] & 0b0000_0111) != 0x00)
+    }
+
+    pub fn get_threshold(&mut self, pad: Pad) -> Result<u8, I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c.write_read(
+            self.address,
+            &[SENSOR_INPUT_1_THRESHOLD + pad.index()],
+            &mut data,
+        )?;
+        Ok(data[0])
+    }
+
+    /// Sets the delta count at which `pad` registers a touch.  `threshold` must not exceed
+    /// `0x7F`.
+    pub fn set_threshold(&mut self, pad: Pad, threshold: u8) -> Result<(), Error<I2C::Error>> {
+        if threshold > 0x7F {
+            return Err(Error::InvalidThreshold);
+        }
+        self.i2c.write(
+            self.address,
+            &[SENSOR_INPUT_1_THRESHOLD + pad.index(), threshold],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_averaging(
+        &mut self,
+        averaging: Averaging,
+        sample_time: SampleTime,
+        cycle_time: CycleTime,
+    ) -> Result<(), I2C::Error> {
+        self.i2c.write(
+            self.address,
+            &[
+                AVERAGING_AND_SAMPLING_CONFIG,
+                <Averaging as core::convert::Into<u8>>::into(averaging)
+                    | <SampleTime as core::convert::Into<u8>>::into(sample_time)
+                    | <CycleTime as core::convert::Into<u8>>::into(cycle_time),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Reads which pads have noise above the noise threshold.  Touches are not reported for
+    /// noisy pads.
+    pub fn read_noise_flags(&mut self) -> Result<(bool, bool, bool), I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[NOISE_FLAG_STATUS], &mut data)?;
+        Ok((
+            (data[0] & 0b0000_0001) != 0x00,
+            (data[0] & 0b0000_0010) != 0x00,
+            (data[0] & 0b0000_0100) != 0x00,
+        ))
+    }
+
+    pub fn set_power_mode(&mut self, power_mode: PowerMode) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[MAIN_CONTROL], &mut data)?;
+        let control = match power_mode {
+            PowerMode::Active => data[0] & 0b1100_1111,
+            PowerMode::Standby => (data[0] & 0b1100_1111) | 0b0010_0000,
+            PowerMode::DeepSleep => (data[0] & 0b1100_1111) | 0b0001_0000,
+        };
+        self.i2c.write(self.address, &[MAIN_CONTROL, control])?;
+        Ok(())
+    }
+
+    /// Selects which pads remain active in [`PowerMode::Standby`].
+    pub fn set_standby_channels(&mut self, pads: (bool, bool, bool)) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[STANDBY_CHANNEL, pad_mask(pads)])?;
+        Ok(())
+    }
+
+    /// Sets the delta count at which a pad registers a touch in [`PowerMode::Standby`].
+    /// `threshold` must not exceed `0x7F`.
+    pub fn set_standby_threshold(&mut self, threshold: u8) -> Result<(), Error<I2C::Error>> {
+        if threshold > 0x7F {
+            return Err(Error::InvalidThreshold);
+        }
+        self.i2c
+            .write(self.address, &[STANDBY_THRESHOLD, threshold])?;
+        Ok(())
+    }
+
+    /// Reads the pads and compares them against the previous poll, producing touch and release
+    /// edges along with any slide gesture that has been completed.
+    pub fn poll(&mut self) -> Result<Events, I2C::Error> {
+        let touched = self.read()?;
+        Ok(self.tracker.update(touched))
+    }
+
     /// # Errors
     pub fn read_delta_counts(&mut self) -> Result<(i8, i8, i8), I2C::Error> {
         let mut data_0: [u8; 1] = [0];
@@ -154,7 +539,10 @@ mod test {
     use embedded_hal::i2c::ErrorKind;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p12::{Error, P12};
+    use crate::p12::{
+        Averaging, CycleTime, Error, Event, Pad, PowerMode, SampleTime, SlideDirection, TouchMode,
+        Tracker, P12,
+    };
     use crate::Driver;
 
     #[test]
@@ -179,7 +567,11 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.get_sensitivity(), Ok(0x87));
 
         i2c_clone.done();
@@ -194,7 +586,11 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         p12.set_sensitivity(4).unwrap();
 
         i2c_clone.done();
@@ -208,7 +604,11 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.set_sensitivity(4), Err(Error::I2cError(i2c_error)));
 
         i2c_clone.done();
@@ -220,7 +620,11 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.set_sensitivity(10), Err(Error::InvalidSensitivity));
 
         i2c_clone.done();
@@ -229,30 +633,60 @@ mod test {
     #[test]
     pub fn clear_interrupt() {
         let expectations = [
-            I2cTransaction::write(0x28, vec![0x00, 0x00]),
-            I2cTransaction::write_read(0x28, vec![0x00], vec![0xF4]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0xF5]),
+            I2cTransaction::write(0x28, vec![0x00, 0xF4]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.clear_interrupt(), Ok(0xF4));
 
         i2c_clone.done();
     }
 
+    #[test]
+    pub fn clear_interrupt_keeps_power_mode() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0x21]),
+            I2cTransaction::write(0x28, vec![0x00, 0x20]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0x11]),
+            I2cTransaction::write(0x28, vec![0x00, 0x10]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.clear_interrupt(), Ok(0x20));
+        assert_eq!(p12.clear_interrupt(), Ok(0x10));
+
+        i2c_clone.done();
+    }
+
     #[test]
     pub fn read() {
         let expectations = [
-            I2cTransaction::write(0x28, vec![0x00, 0x00]),
-            I2cTransaction::write_read(0x28, vec![0x00], vec![0xF4]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0xF5]),
+            I2cTransaction::write(0x28, vec![0x00, 0xF4]),
             I2cTransaction::write_read(0x28, vec![0x02], vec![0xC4]),
             I2cTransaction::write_read(0x28, vec![0x03], vec![0b0000_0101]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.read(), Ok((true, false, true)));
 
         i2c_clone.done();
@@ -268,9 +702,383 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p12 = P12 { i2c, address: 0x28 };
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
         assert_eq!(p12.read_delta_counts(), Ok((-5, -56, 93)));
 
         i2c_clone.done();
     }
+
+    #[test]
+    pub fn set_touch_mode_single() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x2A], vec![0x04]),
+            I2cTransaction::write(0x28, vec![0x2A, 0x80]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_touch_mode(TouchMode::Single).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_interrupt_enabled() {
+        let expectations = [I2cTransaction::write(0x28, vec![0x27, 0b0000_0101])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_interrupt_enabled((true, false, true)).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_interrupt_on_release() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x44], vec![0x40]),
+            I2cTransaction::write(0x28, vec![0x44, 0x41]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_interrupt_on_release(false).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn interrupt_pending() {
+        let expectations = [I2cTransaction::write_read(0x28, vec![0x00], vec![0x01])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.interrupt_pending(), Ok(true));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_repeat_rate() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x22], vec![0xA4]),
+            I2cTransaction::write(0x28, vec![0x22, 0xA1]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_repeat_rate(70).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_long_press_duration() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x23], vec![0x07]),
+            I2cTransaction::write(0x28, vec![0x23, 0x0F]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_long_press_duration(560).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_long_press_duration_error() {
+        let expectations = [];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(
+            p12.set_long_press_duration(100),
+            Err(Error::InvalidDuration)
+        );
+        assert_eq!(p12.set_repeat_rate(595), Err(Error::InvalidDuration));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_repeat_enabled() {
+        let expectations = [I2cTransaction::write(0x28, vec![0x28, 0b0000_0010])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_repeat_enabled((false, true, false)).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn poll() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0x01]),
+            I2cTransaction::write(0x28, vec![0x00, 0x00]),
+            I2cTransaction::write_read(0x28, vec![0x02], vec![0x01]),
+            I2cTransaction::write_read(0x28, vec![0x03], vec![0b0000_0001]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0x01]),
+            I2cTransaction::write(0x28, vec![0x00, 0x00]),
+            I2cTransaction::write_read(0x28, vec![0x02], vec![0x01]),
+            I2cTransaction::write_read(0x28, vec![0x03], vec![0b0000_0100]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert!(p12.poll().unwrap().eq([Event::Touch(Pad::One)]));
+        assert!(p12
+            .poll()
+            .unwrap()
+            .eq([Event::Release(Pad::One), Event::Touch(Pad::Three)]));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn slide() {
+        let mut tracker = Tracker::default();
+        assert!(tracker
+            .update((false, false, true))
+            .eq([Event::Touch(Pad::Three)]));
+        assert!(tracker
+            .update((false, true, false))
+            .eq([Event::Release(Pad::Three), Event::Touch(Pad::Two)]));
+        assert!(tracker.update((true, false, false)).eq([
+            Event::Release(Pad::Two),
+            Event::Touch(Pad::One),
+            Event::Slide(SlideDirection::Backward)
+        ]));
+        assert!(tracker
+            .update((false, false, false))
+            .eq([Event::Release(Pad::One)]));
+    }
+
+    #[test]
+    pub fn no_slide_when_skipping_a_pad() {
+        let mut tracker = Tracker::default();
+        tracker.update((true, false, false));
+        tracker.update((false, false, false));
+        tracker.update((false, false, true));
+        tracker.update((false, false, false));
+        assert!(tracker
+            .update((false, true, false))
+            .eq([Event::Touch(Pad::Two)]));
+    }
+
+    #[test]
+    pub fn read_base_counts() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x50], vec![0xC8]),
+            I2cTransaction::write_read(0x28, vec![0x51], vec![0xC9]),
+            I2cTransaction::write_read(0x28, vec![0x52], vec![0xCA]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.read_base_counts(), Ok((0xC8, 0xC9, 0xCA)));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn calibrate() {
+        let expectations = [
+            I2cTransaction::write(0x28, vec![0x26, 0b0000_0111]),
+            I2cTransaction::write_read(0x28, vec![0x26], vec![0b0000_0010]),
+            I2cTransaction::write_read(0x28, vec![0x26], vec![0b0000_0000]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.calibrate((true, true, true)).unwrap();
+        assert_eq!(p12.is_calibrating(), Ok(true));
+        assert_eq!(p12.is_calibrating(), Ok(false));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn get_threshold() {
+        let expectations = [I2cTransaction::write_read(0x28, vec![0x31], vec![0x40])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.get_threshold(Pad::Two), Ok(0x40));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_threshold() {
+        let expectations = [I2cTransaction::write(0x28, vec![0x32, 0x20])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_threshold(Pad::Three, 0x20).unwrap();
+        assert_eq!(
+            p12.set_threshold(Pad::One, 0x80),
+            Err(Error::InvalidThreshold)
+        );
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_averaging() {
+        let expectations = [I2cTransaction::write(0x28, vec![0x24, 0b0101_1101])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_averaging(
+            Averaging::Samples32,
+            SampleTime::Milliseconds2_56,
+            CycleTime::Milliseconds70,
+        )
+        .unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_noise_flags() {
+        let expectations = [I2cTransaction::write_read(
+            0x28,
+            vec![0x0A],
+            vec![0b0000_0110],
+        )];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.read_noise_flags(), Ok((false, true, true)));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_power_mode() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0b0100_0000]),
+            I2cTransaction::write(0x28, vec![0x00, 0b0110_0000]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0b0110_0000]),
+            I2cTransaction::write(0x28, vec![0x00, 0b0101_0000]),
+            I2cTransaction::write_read(0x28, vec![0x00], vec![0b0101_0000]),
+            I2cTransaction::write(0x28, vec![0x00, 0b0100_0000]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_power_mode(PowerMode::Standby).unwrap();
+        p12.set_power_mode(PowerMode::DeepSleep).unwrap();
+        p12.set_power_mode(PowerMode::Active).unwrap();
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_standby_channels() {
+        let expectations = [
+            I2cTransaction::write(0x28, vec![0x40, 0b0000_0001]),
+            I2cTransaction::write(0x28, vec![0x43, 0x30]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        p12.set_standby_channels((true, false, false)).unwrap();
+        p12.set_standby_threshold(0x30).unwrap();
+
+        i2c_clone.done();
+    }
 }
+
+pub mod whoami;
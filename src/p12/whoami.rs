@@ -0,0 +1,51 @@
+use crate::{p12::P12, WhoAmI};
+use embedded_hal::i2c::I2c;
+
+const PRODUCT_ID: u8 = 0xFD;
+const MANUFACTURER_ID: u8 = 0xFE;
+
+impl<I2C: I2c> WhoAmI<I2C, (u8, u8)> for P12<I2C> {
+    /// The product ID followed by the manufacturer ID.
+    const EXPECTED_WHOAMI: (u8, u8) = (0x6D, 0x5D);
+
+    fn whoami(&mut self) -> Result<(u8, u8), I2C::Error> {
+        let mut product_data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[PRODUCT_ID], &mut product_data)?;
+        let mut manufacturer_data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[MANUFACTURER_ID], &mut manufacturer_data)?;
+        Ok((product_data[0], manufacturer_data[0]))
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod whoami_test {
+    extern crate std;
+    use std::vec;
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    use crate::p12::{Tracker, P12};
+    use crate::WhoAmI;
+
+    #[test]
+    pub fn whoami() {
+        let expectations = [
+            I2cTransaction::write_read(0x28, vec![0xFD], vec![0x6D]),
+            I2cTransaction::write_read(0x28, vec![0xFE], vec![0x5D]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p12 = P12 {
+            i2c,
+            address: 0x28,
+            tracker: Tracker::default(),
+        };
+        assert_eq!(p12.whoami(), Ok(P12::<I2cMock>::EXPECTED_WHOAMI));
+
+        i2c_clone.done();
+    }
+}
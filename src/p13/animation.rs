@@ -0,0 +1,173 @@
+//! Tick driven animations for [`P13`](super::P13).
+//!
+//! Each call to [`Animator::next_frame`] (or [`P13::animate`](super::P13::animate)) advances an
+//! animation by one tick, so the tick rate is chosen by however often the caller polls.
+
+use super::LEN;
+use smart_leds_trait::RGB8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Animation {
+    /// Fades every LED from `from` to `to` over `ticks`, then holds `to`.
+    Fade { from: RGB8, to: RGB8, ticks: u32 },
+    /// Ramps every LED up to `color` and back down to off once per `period` ticks.
+    Breathe { color: RGB8, period: u32 },
+    /// Cycles around the colour wheel once per `period` ticks.  The LEDs are spaced a third of the
+    /// wheel apart.
+    Rainbow { period: u32 },
+    /// Shows `color` on every LED for `on` ticks, then turns them off for `off` ticks.
+    Blink { color: RGB8, on: u32, off: u32 },
+}
+
+fn lerp(from: u8, to: u8, numerator: u32, denominator: u32) -> u8 {
+    let value = i64::from(from)
+        + (i64::from(to) - i64::from(from)) * i64::from(numerator) / i64::from(denominator.max(1));
+    u8::try_from(value).unwrap_or(u8::MAX)
+}
+
+fn scale(color: RGB8, numerator: u32, denominator: u32) -> RGB8 {
+    RGB8::new(
+        lerp(0, color.r, numerator, denominator),
+        lerp(0, color.g, numerator, denominator),
+        lerp(0, color.b, numerator, denominator),
+    )
+}
+
+const fn wheel(position: u8) -> RGB8 {
+    match position {
+        0..=84 => RGB8::new(255 - position * 3, position * 3, 0),
+        85..=169 => RGB8::new(0, 255 - (position - 85) * 3, (position - 85) * 3),
+        _ => RGB8::new((position - 170) * 3, 0, 255 - (position - 170) * 3),
+    }
+}
+
+impl Animation {
+    /// The colours of every LED at `tick`.
+    #[must_use]
+    pub fn frame(&self, tick: u32) -> [RGB8; LEN] {
+        match *self {
+            Self::Fade { from, to, ticks } => {
+                let elapsed = tick.min(ticks);
+                [RGB8::new(
+                    lerp(from.r, to.r, elapsed, ticks),
+                    lerp(from.g, to.g, elapsed, ticks),
+                    lerp(from.b, to.b, elapsed, ticks),
+                ); LEN]
+            }
+            Self::Breathe { color, period } => {
+                let half = (period / 2).max(1);
+                let phase = tick % (half * 2);
+                let level = if phase < half {
+                    phase
+                } else {
+                    half * 2 - phase
+                };
+                [scale(color, level, half); LEN]
+            }
+            Self::Rainbow { period } => {
+                let offset = u64::from(tick % period.max(1)) * 256 / u64::from(period.max(1));
+                let mut frame = [RGB8::default(); LEN];
+                for (pixel, spacing) in frame.iter_mut().zip((0_u64..).step_by(85)) {
+                    *pixel = wheel(u8::try_from((offset + spacing) % 256).unwrap_or(0));
+                }
+                frame
+            }
+            Self::Blink { color, on, off } => {
+                let cycle = on.saturating_add(off).max(1);
+                if tick % cycle < on {
+                    [color; LEN]
+                } else {
+                    [RGB8::default(); LEN]
+                }
+            }
+        }
+    }
+}
+
+/// Tracks the current tick of an [`Animation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Animator {
+    animation: Animation,
+    tick: u32,
+}
+
+impl Animator {
+    #[must_use]
+    pub const fn new(animation: Animation) -> Self {
+        Self { animation, tick: 0 }
+    }
+
+    /// Returns the frame for the current tick and advances to the next tick.
+    pub fn next_frame(&mut self) -> [RGB8; LEN] {
+        let frame = self.animation.frame(self.tick);
+        self.tick = self.tick.wrapping_add(1);
+        frame
+    }
+
+    /// Restarts the animation from its first tick.
+    pub const fn reset(&mut self) {
+        self.tick = 0;
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    use smart_leds_trait::RGB8;
+
+    use crate::p13::animation::{Animation, Animator};
+
+    #[test]
+    pub fn fade() {
+        let animation = Animation::Fade {
+            from: RGB8::new(0, 100, 200),
+            to: RGB8::new(100, 100, 0),
+            ticks: 4,
+        };
+        assert_eq!(animation.frame(0), [RGB8::new(0, 100, 200); 3]);
+        assert_eq!(animation.frame(1), [RGB8::new(25, 100, 150); 3]);
+        assert_eq!(animation.frame(4), [RGB8::new(100, 100, 0); 3]);
+        assert_eq!(animation.frame(10), [RGB8::new(100, 100, 0); 3]);
+    }
+
+    #[test]
+    pub fn breathe() {
+        let animation = Animation::Breathe {
+            color: RGB8::new(200, 0, 100),
+            period: 4,
+        };
+        assert_eq!(animation.frame(0), [RGB8::new(0, 0, 0); 3]);
+        assert_eq!(animation.frame(1), [RGB8::new(100, 0, 50); 3]);
+        assert_eq!(animation.frame(2), [RGB8::new(200, 0, 100); 3]);
+        assert_eq!(animation.frame(3), [RGB8::new(100, 0, 50); 3]);
+        assert_eq!(animation.frame(4), [RGB8::new(0, 0, 0); 3]);
+    }
+
+    #[test]
+    pub fn rainbow() {
+        let animation = Animation::Rainbow { period: 256 };
+        assert_eq!(
+            animation.frame(0),
+            [
+                RGB8::new(255, 0, 0),
+                RGB8::new(0, 255, 0),
+                RGB8::new(0, 0, 255)
+            ]
+        );
+        assert_eq!(animation.frame(0), animation.frame(256));
+    }
+
+    #[test]
+    pub fn blink() {
+        let mut animator = Animator::new(Animation::Blink {
+            color: RGB8::new(1, 2, 3),
+            on: 2,
+            off: 1,
+        });
+        assert_eq!(animator.next_frame(), [RGB8::new(1, 2, 3); 3]);
+        assert_eq!(animator.next_frame(), [RGB8::new(1, 2, 3); 3]);
+        assert_eq!(animator.next_frame(), [RGB8::default(); 3]);
+        assert_eq!(animator.next_frame(), [RGB8::new(1, 2, 3); 3]);
+        animator.reset();
+        assert_eq!(animator.next_frame(), [RGB8::new(1, 2, 3); 3]);
+    }
+}
@@ -54,6 +54,7 @@ mod atmel_test {
     extern crate embedded_hal;
     extern crate embedded_hal_mock;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use smart_leds_trait::RGB8;
 
     use crate::p13::P13;
     use crate::{Atmel, SetAddressError};
@@ -64,7 +65,11 @@ mod atmel_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
 
         assert_eq!(p13.set_led(true), Ok(()));
         i2c_clone.done();
@@ -76,7 +81,11 @@ mod atmel_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
 
         assert_eq!(p13.set_led(false), Ok(()));
         i2c_clone.done();
@@ -88,7 +97,11 @@ mod atmel_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
 
         assert_eq!(p13.get_led(), Ok(false));
         i2c_clone.done();
@@ -100,7 +113,11 @@ mod atmel_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
 
         assert_eq!(p13.get_led(), Ok(true));
         i2c_clone.done();
@@ -115,7 +132,11 @@ mod atmel_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
 
         assert_eq!(p13.firmware(), Ok((0x03, 0x02)));
         i2c_clone.done();
@@ -127,7 +148,11 @@ mod atmel_test {
 
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
         p13.set_address(0x69).unwrap();
 
         i2c_clone.done();
@@ -139,7 +164,11 @@ mod atmel_test {
 
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
         assert_eq!(p13.set_address(0x07), Err(SetAddressError::ArgumentError));
 
         i2c_clone.done();
@@ -151,7 +180,11 @@ mod atmel_test {
 
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
         assert_eq!(p13.set_address(0x78), Err(SetAddressError::ArgumentError));
 
         i2c_clone.done();
@@ -15,11 +15,21 @@
 use crate::Driver;
 use core::num::TryFromIntError;
 use embedded_hal::i2c::I2c;
-use smart_leds_trait::SmartLedsWrite;
+use smart_leds_trait::{SmartLedsWrite, RGB8};
+
+const REG_CLEAR: u8 = 0x04;
+const REG_BRIGHTNESS: u8 = 0x06;
+const REG_LED_VALUES: u8 = 0x07;
+
+const DEFAULT_BRIGHTNESS: u8 = 50;
+
+/// The number of LEDs on the module.
+pub const LEN: usize = 3;
 
 pub struct P13<I2C> {
     i2c: I2C,
     address: u8,
+    pixels: [RGB8; LEN],
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -35,34 +45,85 @@ impl<E> From<E> for Error<E> {
     }
 }
 
-impl<I2C: I2c> Driver<I2C, core::convert::Infallible> for P13<I2C> {
+impl<I2C: I2c> Driver<I2C, I2C::Error> for P13<I2C> {
     fn new_inner(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            pixels: [RGB8::default(); LEN],
+        }
+    }
+
+    fn init_inner(mut self) -> Result<Self, I2C::Error> {
+        self.set_brightness(DEFAULT_BRIGHTNESS)?;
+        self.show()?;
+        Ok(self)
+    }
+}
+
+impl<I2C: I2c> P13<I2C> {
+    /// Sets the brightness applied by the firmware to every LED.
+    pub fn set_brightness(&mut self, brightness: u8) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[REG_BRIGHTNESS, brightness])?;
+        Ok(())
+    }
+
+    /// Sets a single LED in the buffer.  Nothing changes on the module until [`P13::show`] is
+    /// called.
+    pub fn set_pixel(&mut self, index: usize, color: RGB8) -> Result<(), Error<I2C::Error>> {
+        *self.pixels.get_mut(index).ok_or(Error::ArgumentError)? = color;
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn pixels(&self) -> [RGB8; LEN] {
+        self.pixels
+    }
+
+    /// Sets every LED in the buffer.  Nothing changes on the module until [`P13::show`] is
+    /// called.
+    pub const fn fill(&mut self, color: RGB8) {
+        self.pixels = [color; LEN];
+    }
+
+    /// Sends the buffer to the module.
+    pub fn show(&mut self) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1 + 3 * LEN] = [REG_LED_VALUES; 1 + 3 * LEN];
+        for (chunk, pixel) in data[1..].chunks_exact_mut(3).zip(self.pixels) {
+            chunk.copy_from_slice(&[pixel.r, pixel.g, pixel.b]);
+        }
+        self.i2c.write(self.address, &data)?;
+        Ok(())
+    }
+
+    /// Turns every LED off, both on the module and in the buffer.
+    pub fn clear(&mut self) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[REG_CLEAR, 0x01])?;
+        self.pixels = [RGB8::default(); LEN];
+        Ok(())
+    }
+
+    /// Advances `animator` by one tick and shows the resulting frame.
+    pub fn animate(&mut self, animator: &mut animation::Animator) -> Result<(), I2C::Error> {
+        self.pixels = animator.next_frame();
+        self.show()
     }
 }
 
 impl<I2C: I2c> SmartLedsWrite for P13<I2C> {
-    type Color = smart_leds_trait::RGB8;
+    type Color = RGB8;
     type Error = I2C::Error;
 
     fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
     where
-        T: Iterator<Item = I>,
+        T: IntoIterator<Item = I>,
         I: Into<Self::Color>,
     {
-        let mut data: [u8; 10] = [0x07, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
-        for (i, v) in iterator
-            .flat_map(|item| {
-                let color: Self::Color = item.into();
-                [color.r, color.g, color.b]
-            })
-            .enumerate()
-        {
-            data[i + 1] = v;
+        for (pixel, item) in self.pixels.iter_mut().zip(iterator) {
+            *pixel = item.into();
         }
-        self.i2c.write(self.address, &data)?;
-
-        Ok(())
+        self.show()
     }
 }
 
@@ -75,7 +136,7 @@ mod test {
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
     use smart_leds_trait::{SmartLedsWrite, RGB};
 
-    use crate::p13::P13;
+    use crate::p13::{animation::Animation, animation::Animator, Error, P13};
     use crate::Driver;
 
     #[test]
@@ -109,7 +170,101 @@ mod test {
         p13.write(data.iter().copied()).unwrap();
         i2c_clone.done();
     }
+
+    #[test]
+    pub fn init() {
+        let expectations = [
+            I2cTransaction::write(0x08, vec![0x06, 0x32]),
+            I2cTransaction::write(
+                0x08,
+                vec![0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        P13::new(i2c, 0x08).unwrap().init().unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_brightness() {
+        let expectations = [I2cTransaction::write(0x08, vec![0x06, 0xC8])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p13 = P13::new(i2c, 0x08).unwrap();
+        p13.set_brightness(200).unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_pixel_and_show() {
+        let expectations = [I2cTransaction::write(
+            0x08,
+            vec![0x07, 0x01, 0x02, 0x03, 0x00, 0x00, 0x00, 0x04, 0x05, 0x06],
+        )];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p13 = P13::new(i2c, 0x08).unwrap();
+        p13.set_pixel(0, RGB::new(0x01, 0x02, 0x03)).unwrap();
+        p13.set_pixel(2, RGB::new(0x04, 0x05, 0x06)).unwrap();
+        assert_eq!(
+            p13.set_pixel(3, RGB::new(0x07, 0x08, 0x09)),
+            Err(Error::ArgumentError)
+        );
+        p13.show().unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn fill_and_clear() {
+        let expectations = [
+            I2cTransaction::write(
+                0x08,
+                vec![0x07, 0x10, 0x20, 0x30, 0x10, 0x20, 0x30, 0x10, 0x20, 0x30],
+            ),
+            I2cTransaction::write(0x08, vec![0x04, 0x01]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p13 = P13::new(i2c, 0x08).unwrap();
+        p13.fill(RGB::new(0x10, 0x20, 0x30));
+        p13.show().unwrap();
+        p13.clear().unwrap();
+        assert_eq!(p13.pixels(), [RGB::default(); 3]);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn animate() {
+        let expectations = [
+            I2cTransaction::write(
+                0x08,
+                vec![0x07, 0xFF, 0x00, 0x00, 0xFF, 0x00, 0x00, 0xFF, 0x00, 0x00],
+            ),
+            I2cTransaction::write(
+                0x08,
+                vec![0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p13 = P13::new(i2c, 0x08).unwrap();
+        let mut animator = Animator::new(Animation::Blink {
+            color: RGB::new(0xFF, 0x00, 0x00),
+            on: 1,
+            off: 1,
+        });
+        p13.animate(&mut animator).unwrap();
+        p13.animate(&mut animator).unwrap();
+        i2c_clone.done();
+    }
 }
 
+pub mod animation;
 pub mod atmel;
 pub mod whoami;
@@ -21,6 +21,7 @@ mod whoami_test {
     extern crate embedded_hal;
     extern crate embedded_hal_mock;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use smart_leds_trait::RGB8;
 
     use crate::{p13::P13, WhoAmI};
 
@@ -30,7 +31,11 @@ mod whoami_test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p13 = P13 { i2c, address: 0x09 };
+        let mut p13 = P13 {
+            i2c,
+            address: 0x09,
+            pixels: [RGB8::default(); 3],
+        };
         assert_eq!(p13.whoami(), Ok(2));
 
         i2c_clone.done();
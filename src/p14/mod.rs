@@ -13,110 +13,341 @@
 //! [Datasheet]: https://cdn-shop.adafruit.com/datasheets/SSD1306.pdf
 
 use crate::Driver;
-use embedded_graphics::{
-    draw_target::DrawTarget, geometry::Size, pixelcolor::BinaryColor, prelude::OriginDimensions,
+use core::marker::PhantomData;
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
     Pixel,
 };
 use embedded_hal::i2c::I2c;
+use num_enum::IntoPrimitive;
+use size::{DisplaySize, DisplaySize128x64};
 
 const SET_COL_ADDR: u8 = 0x21;
 const SET_PAGE_ADDR: u8 = 0x22;
-const WIDTH: u8 = 128;
-const HEIGHT: u8 = 64;
-const PAGES: u8 = HEIGHT / 8;
-const BUFFER_SIZE: usize = PAGES as usize * WIDTH as usize;
-const INIT_COMMANDS: [u8; 27] = [
-    // set disp
-    0xAE,
-    // set mem addr
-    0x20,
-    0x00,
-    // set disp start line
-    0x40,
-    // set seg mremap
-    0xA0 | 0x01,
-    // set mux ratio
-    0xA8,
-    (HEIGHT - 1),
-    // set com out dir
-    0xC0 | 0x08,
-    // set disp offset
-    0xD3,
-    0x00,
-    // set com pin cfg
-    0xDA,
-    0x12,
-    // set disp clk div
-    0xD5,
-    0x80,
-    // set precharge
-    0xD9,
-    0xF1,
-    // set vcom desel
-    0xDB,
-    0x30,
-    // set contrast
-    0x81,
-    0xFF,
-    // set entire on
-    0xA4,
-    // set norm inv
-    0xA6,
-    // set iref select
-    0xAD,
-    0x30,
-    // set charge pump
-    0x8D,
-    0x14,
-    // set disp
-    0xAE | 0x01,
-];
-
-pub struct P14<I2C> {
+const DEACTIVATE_SCROLL: u8 = 0x2E;
+const ACTIVATE_SCROLL: u8 = 0x2F;
+const SET_CONTRAST: u8 = 0x81;
+const SET_SEG_REMAP: u8 = 0xA0;
+const SET_VERTICAL_SCROLL_AREA: u8 = 0xA3;
+const SET_NORM_INV: u8 = 0xA6;
+const SET_DISP: u8 = 0xAE;
+const SET_COM_OUT_DIR: u8 = 0xC0;
+const MAX_PAGES: usize = 8;
+const MAX_BUFFER_SIZE: usize = 1024;
+
+const fn init_commands(height: u8, com_pins: u8) -> [u8; 27] {
+    [
+        // set disp
+        0xAE,
+        // set mem addr
+        0x20,
+        0x00,
+        // set disp start line
+        0x40,
+        // set seg mremap
+        0xA0 | 0x01,
+        // set mux ratio
+        0xA8,
+        height - 1,
+        // set com out dir
+        0xC0 | 0x08,
+        // set disp offset
+        0xD3,
+        0x00,
+        // set com pin cfg
+        0xDA,
+        com_pins,
+        // set disp clk div
+        0xD5,
+        0x80,
+        // set precharge
+        0xD9,
+        0xF1,
+        // set vcom desel
+        0xDB,
+        0x30,
+        // set contrast
+        0x81,
+        0xFF,
+        // set entire on
+        0xA4,
+        // set norm inv
+        0xA6,
+        // set iref select
+        0xAD,
+        0x30,
+        // set charge pump
+        0x8D,
+        0x14,
+        // set disp
+        0xAE | 0x01,
+    ]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    I2cError(E),
+    ArgumentError,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Self::I2cError(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Degrees0,
+    Degrees180,
+}
+
+#[derive(IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ScrollDirection {
+    Right = 0x00,
+    Left = 0x01,
+}
+
+/// The number of frames between each step of a hardware scroll.
+#[derive(IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ScrollInterval {
+    Frames2 = 0b111,
+    Frames3 = 0b100,
+    Frames4 = 0b101,
+    Frames5 = 0b000,
+    Frames25 = 0b110,
+    Frames64 = 0b001,
+    Frames128 = 0b010,
+    Frames256 = 0b011,
+}
+
+// The inclusive range of columns changed on each page since it was last sent to the display.
+type Dirty = [Option<(u8, u8)>; MAX_PAGES];
+
+const CLEAN: Dirty = [None; MAX_PAGES];
+
+const fn all_dirty(width: u8, pages: u8) -> Dirty {
+    let mut dirty = CLEAN;
+    let mut page = 0;
+    while page < pages as usize {
+        dirty[page] = Some((0, width - 1));
+        page += 1;
+    }
+    dirty
+}
+
+/// The size of the panel is chosen through `S`, defaulting to the 128x64 panel on the `PiicoDev`
+/// OLED module.  e.g. `P14::<_, DisplaySize128x32>::new(i2c, 0x3C)`
+pub struct P14<I2C, S: DisplaySize = DisplaySize128x64> {
     i2c: I2C,
     address: u8,
-    framebuffer: [u8; BUFFER_SIZE],
+    framebuffer: S::Buffer,
+    dirty: Dirty,
+    size: PhantomData<S>,
 }
 
-impl<I2C: I2c> Driver<I2C, I2C::Error> for P14<I2C> {
+impl<I2C: I2c, S: DisplaySize> Driver<I2C, I2C::Error> for P14<I2C, S> {
     fn new_inner(i2c: I2C, address: u8) -> Self {
         Self {
             i2c,
             address,
-            framebuffer: [0; BUFFER_SIZE],
+            framebuffer: S::buffer(),
+            dirty: Self::ALL_DIRTY,
+            size: PhantomData,
         }
     }
 
     fn init_inner(mut self) -> Result<Self, I2C::Error> {
-        for command in INIT_COMMANDS {
-            self.i2c.write(self.address, &[0x80, command])?;
-        }
+        self.write_commands(&init_commands(S::HEIGHT, S::COM_PINS))?;
         Ok(self)
     }
 }
 
-impl<I2C: I2c> P14<I2C> {
+impl<I2C: I2c, S: DisplaySize> P14<I2C, S> {
+    const PAGES: u8 = S::HEIGHT / 8;
+    const ALL_DIRTY: Dirty = all_dirty(S::WIDTH, Self::PAGES);
+
+    fn write_commands(&mut self, commands: &[u8]) -> Result<(), I2C::Error> {
+        for command in commands {
+            self.i2c.write(self.address, &[0x80, *command])?;
+        }
+        Ok(())
+    }
+
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), I2C::Error> {
+        self.write_commands(&[SET_CONTRAST, contrast])
+    }
+
+    /// Inverts every pixel on the display without changing the framebuffer.
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<(), I2C::Error> {
+        self.write_commands(&[SET_NORM_INV | u8::from(inverted)])
+    }
+
+    /// Turns the display panel on or off.  The framebuffer is retained by the display while it is
+    /// off, which draws much less power.
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), I2C::Error> {
+        self.write_commands(&[SET_DISP | u8::from(on)])
+    }
+
+    /// Rotates the display through the segment remap and COM scan direction.  The segment remap
+    /// only applies to data sent afterwards, so the whole framebuffer is sent on the next
+    /// [`P14::show`].
+    pub fn set_rotation(&mut self, rotation: Rotation) -> Result<(), I2C::Error> {
+        match rotation {
+            Rotation::Degrees0 => {
+                self.write_commands(&[SET_SEG_REMAP | 0x01, SET_COM_OUT_DIR | 0x08])?;
+            }
+            Rotation::Degrees180 => {
+                self.write_commands(&[SET_SEG_REMAP, SET_COM_OUT_DIR])?;
+            }
+        }
+        self.dirty = Self::ALL_DIRTY;
+        Ok(())
+    }
+
+    /// Continuously scrolls pages `start_page..=end_page` horizontally until
+    /// [`P14::stop_scroll`] is called.
+    pub fn scroll_horizontal(
+        &mut self,
+        direction: ScrollDirection,
+        start_page: u8,
+        end_page: u8,
+        interval: ScrollInterval,
+    ) -> Result<(), Error<I2C::Error>> {
+        if start_page > end_page || end_page >= Self::PAGES {
+            return Err(Error::ArgumentError);
+        }
+        self.write_commands(&[
+            DEACTIVATE_SCROLL,
+            0x26 | <ScrollDirection as core::convert::Into<u8>>::into(direction),
+            0x00,
+            start_page,
+            interval.into(),
+            end_page,
+            0x00,
+            0xFF,
+            ACTIVATE_SCROLL,
+        ])?;
+        Ok(())
+    }
+
+    /// Continuously scrolls pages `start_page..=end_page` horizontally while scrolling the whole
+    /// display vertically by `vertical_offset` rows each step, until [`P14::stop_scroll`] is
+    /// called.
+    pub fn scroll_diagonal(
+        &mut self,
+        direction: ScrollDirection,
+        start_page: u8,
+        end_page: u8,
+        interval: ScrollInterval,
+        vertical_offset: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        if start_page > end_page || end_page >= Self::PAGES || vertical_offset >= S::HEIGHT {
+            return Err(Error::ArgumentError);
+        }
+        self.write_commands(&[
+            DEACTIVATE_SCROLL,
+            SET_VERTICAL_SCROLL_AREA,
+            0x00,
+            S::HEIGHT,
+            0x29 + <ScrollDirection as core::convert::Into<u8>>::into(direction),
+            0x00,
+            start_page,
+            interval.into(),
+            end_page,
+            vertical_offset,
+            ACTIVATE_SCROLL,
+        ])?;
+        Ok(())
+    }
+
+    /// Stops any hardware scroll.  The display RAM is left scrolled, so the whole framebuffer is
+    /// sent on the next [`P14::show`].
+    pub fn stop_scroll(&mut self) -> Result<(), I2C::Error> {
+        self.write_commands(&[DEACTIVATE_SCROLL])?;
+        self.dirty = Self::ALL_DIRTY;
+        Ok(())
+    }
+
+    /// Sends the parts of the framebuffer that have been drawn to since they were last sent.
+    /// Consecutive changed pages are sent together as a single window spanning their changed
+    /// columns.
     pub fn show(&mut self) -> Result<(), I2C::Error> {
-        let x0: u8 = 0;
-        let x1: u8 = WIDTH - 1;
+        let mut run: Option<(u8, u8, u8, u8)> = None;
+        for (page, columns) in (0..Self::PAGES).zip(self.dirty) {
+            run = match (run, columns) {
+                (Some((x0, x1, page0, _)), Some((c0, c1))) => {
+                    Some((x0.min(c0), x1.max(c1), page0, page))
+                }
+                (None, Some((c0, c1))) => Some((c0, c1, page, page)),
+                (Some((x0, x1, page0, page1)), None) => {
+                    self.send(x0, x1, page0, page1)?;
+                    None
+                }
+                (None, None) => None,
+            };
+        }
+        if let Some((x0, x1, page0, page1)) = run {
+            self.send(x0, x1, page0, page1)?;
+        }
+        self.dirty = CLEAN;
+        Ok(())
+    }
+
+    /// Sends the pages and columns of the framebuffer covering `region`, regardless of whether
+    /// they have changed.  Parts of `region` outside of the display are ignored.
+    pub fn show_region(&mut self, region: &Rectangle) -> Result<(), I2C::Error> {
+        let area = region.intersection(&self.bounding_box());
+        if let Some(bottom_right) = area.bottom_right() {
+            let (Ok(x0), Ok(x1), Ok(y0), Ok(y1)) = (
+                u8::try_from(area.top_left.x),
+                u8::try_from(bottom_right.x),
+                u8::try_from(area.top_left.y),
+                u8::try_from(bottom_right.y),
+            ) else {
+                return Ok(());
+            };
+            let (page0, page1) = (y0 / 8, y1 / 8);
+            self.send(x0, x1, page0, page1)?;
+            for columns in &mut self.dirty[usize::from(page0)..=usize::from(page1)] {
+                if columns.is_some_and(|(c0, c1)| x0 <= c0 && c1 <= x1) {
+                    *columns = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, x0: u8, x1: u8, page0: u8, page1: u8) -> Result<(), I2C::Error> {
         self.i2c.write(self.address, &[0x80, SET_COL_ADDR])?;
-        self.i2c.write(self.address, &[0x80, x0])?;
-        self.i2c.write(self.address, &[0x80, x1])?;
+        self.i2c
+            .write(self.address, &[0x80, x0 + S::COLUMN_OFFSET])?;
+        self.i2c
+            .write(self.address, &[0x80, x1 + S::COLUMN_OFFSET])?;
         self.i2c.write(self.address, &[0x80, SET_PAGE_ADDR])?;
-        self.i2c.write(self.address, &[0x80, 0])?;
-        self.i2c.write(self.address, &[0x80, PAGES - 1])?;
+        self.i2c.write(self.address, &[0x80, page0])?;
+        self.i2c.write(self.address, &[0x80, page1])?;
 
-        let mut i2c_buffer: [u8; 1025] = [0x40; 1025];
-        for (i, val) in self.framebuffer.iter().enumerate() {
-            i2c_buffer[i + 1] = *val;
+        let mut i2c_buffer: [u8; MAX_BUFFER_SIZE + 1] = [0x40; MAX_BUFFER_SIZE + 1];
+        let mut len: usize = 1;
+        for page in page0..=page1 {
+            let start = usize::from(page) * usize::from(S::WIDTH);
+            let row = &self.framebuffer.as_ref()[start + usize::from(x0)..=start + usize::from(x1)];
+            i2c_buffer[len..len + row.len()].copy_from_slice(row);
+            len += row.len();
         }
 
-        self.i2c.write(self.address, &i2c_buffer)?;
+        self.i2c.write(self.address, &i2c_buffer[..len])?;
         Ok(())
     }
 }
 
-impl<I2C: I2c> DrawTarget for P14<I2C> {
+impl<I2C: I2c, S: DisplaySize> DrawTarget for P14<I2C, S> {
     type Color = BinaryColor;
     type Error = core::convert::Infallible;
 
@@ -126,14 +357,24 @@ impl<I2C: I2c> DrawTarget for P14<I2C> {
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         for Pixel(coord, color) in pixels {
-            if let Ok((x @ 0..=127_u32, y @ 0..=63_u32)) = coord.try_into() {
+            if let (Ok(x), Ok(y)) = (u8::try_from(coord.x), u8::try_from(coord.y)) {
+                if x >= S::WIDTH || y >= S::HEIGHT {
+                    continue;
+                }
                 let mask: u8 = 1 << (y % 8);
-                let index: usize = x as usize + (y as usize / 8) * (WIDTH as usize);
+                let page: u8 = y / 8;
+                let index: usize = usize::from(x) + usize::from(page) * usize::from(S::WIDTH);
 
+                let byte = &mut self.framebuffer.as_mut()[index];
+                let previous = *byte;
                 if color.is_on() {
-                    self.framebuffer[index] |= mask;
+                    *byte |= mask;
                 } else {
-                    self.framebuffer[index] &= !mask;
+                    *byte &= !mask;
+                }
+                if *byte != previous {
+                    let columns = &mut self.dirty[usize::from(page)];
+                    *columns = Some(columns.map_or((x, x), |(x0, x1)| (x0.min(x), x1.max(x))));
                 }
             }
         }
@@ -142,9 +383,9 @@ impl<I2C: I2c> DrawTarget for P14<I2C> {
     }
 }
 
-impl<I2C: I2c> OriginDimensions for P14<I2C> {
+impl<I2C: I2c, S: DisplaySize> OriginDimensions for P14<I2C, S> {
     fn size(&self) -> Size {
-        Size::new(WIDTH.try_into().unwrap(), HEIGHT.try_into().unwrap())
+        Size::new(S::WIDTH.into(), S::HEIGHT.into())
     }
 }
 
@@ -162,7 +403,12 @@ mod test {
     };
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p14::P14;
+    use core::marker::PhantomData;
+
+    use crate::p14::{
+        all_dirty, size::DisplaySize128x32, size::DisplaySize72x40, Error, Rotation,
+        ScrollDirection, ScrollInterval, CLEAN, P14,
+    };
     use crate::Driver;
 
     #[test]
@@ -199,7 +445,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        P14::new(i2c, 0x3C).unwrap().init().unwrap();
+        P14::<_>::new(i2c, 0x3C).unwrap().init().unwrap();
         i2c_clone.done();
     }
 
@@ -220,10 +466,12 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p14 = P14 {
+        let mut p14 = P14::<_> {
             i2c,
             address: 0x3C,
             framebuffer: [0x0f; 1024],
+            dirty: all_dirty(128, 8),
+            size: PhantomData,
         };
         p14.show().unwrap();
         i2c_clone.done();
@@ -233,10 +481,12 @@ mod test {
     pub fn size() {
         let i2c = I2cMock::new(&[]);
         let mut i2c_clone = i2c.clone();
-        let p14 = P14 {
+        let p14 = P14::<_> {
             i2c,
             address: 0x3C,
             framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
         };
         assert_eq!(p14.size(), Size::new(128, 64));
         i2c_clone.done();
@@ -246,10 +496,12 @@ mod test {
     pub fn draw_target() {
         let i2c = I2cMock::new(&[]);
         let mut i2c_clone = i2c.clone();
-        let mut p14 = P14 {
+        let mut p14 = P14::<_> {
             i2c,
             address: 0x3C,
             framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
         };
 
         let fill = PrimitiveStyle::with_fill(BinaryColor::On);
@@ -302,4 +554,331 @@ mod test {
         );
         i2c_clone.done();
     }
+
+    #[test]
+    pub fn draw_marks_dirty() {
+        let i2c = I2cMock::new(&[]);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        Pixel(Point::new(5, 9), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(3, 14), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(100, 0), BinaryColor::Off)
+            .draw(&mut p14)
+            .unwrap();
+
+        let mut expected = CLEAN;
+        expected[1] = Some((3, 5));
+        assert_eq!(p14.dirty, expected);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn show_dirty() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x21]),
+            I2cTransaction::write(0x3C, vec![0x80, 3]),
+            I2cTransaction::write(0x3C, vec![0x80, 5]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x22]),
+            I2cTransaction::write(0x3C, vec![0x80, 1]),
+            I2cTransaction::write(0x3C, vec![0x80, 2]),
+            I2cTransaction::write(0x3C, vec![0x40, 0x40, 0x00, 0x02, 0x00, 0x00, 0x01]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x21]),
+            I2cTransaction::write(0x3C, vec![0x80, 127]),
+            I2cTransaction::write(0x3C, vec![0x80, 127]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x22]),
+            I2cTransaction::write(0x3C, vec![0x80, 7]),
+            I2cTransaction::write(0x3C, vec![0x80, 7]),
+            I2cTransaction::write(0x3C, vec![0x40, 0x80]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        Pixel(Point::new(5, 9), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(3, 14), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(5, 16), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(127, 63), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        p14.show().unwrap();
+        assert_eq!(p14.dirty, CLEAN);
+
+        p14.show().unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn show_region() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x21]),
+            I2cTransaction::write(0x3C, vec![0x80, 126]),
+            I2cTransaction::write(0x3C, vec![0x80, 127]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x22]),
+            I2cTransaction::write(0x3C, vec![0x80, 6]),
+            I2cTransaction::write(0x3C, vec![0x80, 7]),
+            I2cTransaction::write(0x3C, vec![0x40, 0x00, 0x00, 0x00, 0x80]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        Pixel(Point::new(127, 63), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        p14.show_region(&Rectangle::new(Point::new(126, 50), Size::new(10, 20)))
+            .unwrap();
+        assert_eq!(p14.dirty, CLEAN);
+        p14.show_region(&Rectangle::new(Point::new(200, 0), Size::new(10, 10)))
+            .unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_contrast() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x81]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x7F]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        p14.set_contrast(0x7F).unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_inverted_and_display_on() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0xA7]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xA6]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xAE]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xAF]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        p14.set_inverted(true).unwrap();
+        p14.set_inverted(false).unwrap();
+        p14.set_display_on(false).unwrap();
+        p14.set_display_on(true).unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_rotation() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0xA0]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xC0]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xA1]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xC8]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        p14.set_rotation(Rotation::Degrees180).unwrap();
+        assert_eq!(p14.dirty, all_dirty(128, 8));
+        p14.set_rotation(Rotation::Degrees0).unwrap();
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn scroll_horizontal() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x2E]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x27]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x00]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x02]),
+            I2cTransaction::write(0x3C, vec![0x80, 0b111]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x05]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x00]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xFF]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x2F]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x2E]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        p14.scroll_horizontal(ScrollDirection::Left, 2, 5, ScrollInterval::Frames2)
+            .unwrap();
+        assert_eq!(
+            p14.scroll_horizontal(ScrollDirection::Left, 2, 8, ScrollInterval::Frames2),
+            Err(Error::ArgumentError)
+        );
+        p14.stop_scroll().unwrap();
+        assert_eq!(p14.dirty, all_dirty(128, 8));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn scroll_diagonal() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x2E]),
+            I2cTransaction::write(0x3C, vec![0x80, 0xA3]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x00]),
+            I2cTransaction::write(0x3C, vec![0x80, 64]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x29]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x00]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x00]),
+            I2cTransaction::write(0x3C, vec![0x80, 0b000]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x07]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x01]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x2F]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p14 = P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        };
+
+        p14.scroll_diagonal(ScrollDirection::Right, 0, 7, ScrollInterval::Frames5, 1)
+            .unwrap();
+        assert_eq!(
+            p14.scroll_diagonal(ScrollDirection::Right, 0, 7, ScrollInterval::Frames5, 64),
+            Err(Error::ArgumentError)
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn new_128x32() {
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 174]),
+            I2cTransaction::write(0x3C, vec![0x80, 32]),
+            I2cTransaction::write(0x3C, vec![0x80, 0]),
+            I2cTransaction::write(0x3C, vec![0x80, 64]),
+            I2cTransaction::write(0x3C, vec![0x80, 161]),
+            I2cTransaction::write(0x3C, vec![0x80, 168]),
+            I2cTransaction::write(0x3C, vec![0x80, 31]),
+            I2cTransaction::write(0x3C, vec![0x80, 200]),
+            I2cTransaction::write(0x3C, vec![0x80, 211]),
+            I2cTransaction::write(0x3C, vec![0x80, 0]),
+            I2cTransaction::write(0x3C, vec![0x80, 218]),
+            I2cTransaction::write(0x3C, vec![0x80, 2]),
+            I2cTransaction::write(0x3C, vec![0x80, 213]),
+            I2cTransaction::write(0x3C, vec![0x80, 128]),
+            I2cTransaction::write(0x3C, vec![0x80, 217]),
+            I2cTransaction::write(0x3C, vec![0x80, 241]),
+            I2cTransaction::write(0x3C, vec![0x80, 219]),
+            I2cTransaction::write(0x3C, vec![0x80, 48]),
+            I2cTransaction::write(0x3C, vec![0x80, 129]),
+            I2cTransaction::write(0x3C, vec![0x80, 255]),
+            I2cTransaction::write(0x3C, vec![0x80, 164]),
+            I2cTransaction::write(0x3C, vec![0x80, 166]),
+            I2cTransaction::write(0x3C, vec![0x80, 173]),
+            I2cTransaction::write(0x3C, vec![0x80, 48]),
+            I2cTransaction::write(0x3C, vec![0x80, 141]),
+            I2cTransaction::write(0x3C, vec![0x80, 20]),
+            I2cTransaction::write(0x3C, vec![0x80, 175]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let p14 = P14::<_, DisplaySize128x32>::new(i2c, 0x3C)
+            .unwrap()
+            .init()
+            .unwrap();
+        assert_eq!(p14.size(), Size::new(128, 32));
+        assert_eq!(p14.dirty, all_dirty(128, 4));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn show_72x40() {
+        let mut v = vec![0x00; 361];
+        v[0] = 0x40;
+        v[360] = 0x80;
+        let expectations = [
+            I2cTransaction::write(0x3C, vec![0x80, 0x21]),
+            I2cTransaction::write(0x3C, vec![0x80, 28]),
+            I2cTransaction::write(0x3C, vec![0x80, 99]),
+            I2cTransaction::write(0x3C, vec![0x80, 0x22]),
+            I2cTransaction::write(0x3C, vec![0x80, 0]),
+            I2cTransaction::write(0x3C, vec![0x80, 4]),
+            I2cTransaction::write(0x3C, v),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p14 = P14::<_, DisplaySize72x40>::new(i2c, 0x3C).unwrap();
+        Pixel(Point::new(71, 39), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(72, 0), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        Pixel(Point::new(0, 40), BinaryColor::On)
+            .draw(&mut p14)
+            .unwrap();
+        p14.show().unwrap();
+        assert_eq!(
+            p14.scroll_horizontal(ScrollDirection::Left, 0, 5, ScrollInterval::Frames2),
+            Err(Error::ArgumentError)
+        );
+        i2c_clone.done();
+    }
 }
+
+mod font;
+pub mod size;
+pub mod terminal;
@@ -0,0 +1,51 @@
+//! Panel geometries for [`P14`](super::P14).  The SSD1306 drives up to 128x64 pixels; smaller
+//! panels are wired to a window of its display RAM.
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The geometry of an SSD1306 panel.  Sealed, as the driver relies on the geometry fitting the
+/// SSD1306 display RAM.
+pub trait DisplaySize: sealed::Sealed {
+    const WIDTH: u8;
+    /// Must be a multiple of 8.
+    const HEIGHT: u8;
+    /// The first column of display RAM that is visible on the panel.
+    const COLUMN_OFFSET: u8;
+    /// The COM pins hardware configuration for how the panel rows are wired.
+    const COM_PINS: u8;
+
+    /// A framebuffer of `WIDTH * HEIGHT / 8` bytes.
+    type Buffer: AsRef<[u8]> + AsMut<[u8]>;
+
+    fn buffer() -> Self::Buffer;
+}
+
+macro_rules! display_size {
+    ($name:ident, $width:literal, $height:literal, $column_offset:literal, $com_pins:literal) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name;
+
+        impl sealed::Sealed for $name {}
+
+        impl DisplaySize for $name {
+            const WIDTH: u8 = $width;
+            const HEIGHT: u8 = $height;
+            const COLUMN_OFFSET: u8 = $column_offset;
+            const COM_PINS: u8 = $com_pins;
+
+            type Buffer = [u8; $width * $height / 8];
+
+            fn buffer() -> Self::Buffer {
+                [0; $width * $height / 8]
+            }
+        }
+    };
+}
+
+display_size!(DisplaySize128x64, 128, 64, 0, 0x12);
+display_size!(DisplaySize128x32, 128, 32, 0, 0x02);
+display_size!(DisplaySize96x16, 96, 16, 0, 0x02);
+display_size!(DisplaySize72x40, 72, 40, 28, 0x12);
+display_size!(DisplaySize64x48, 64, 48, 32, 0x12);
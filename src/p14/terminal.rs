@@ -0,0 +1,216 @@
+//! A text console for [`P14`] using a built-in 5x8 font.
+//!
+//! Characters are laid out in 6x8 cells (one column of spacing) aligned to the display pages,
+//! giving 21 columns by 8 rows on a 128x64 panel.  Text wraps at the right edge and the console
+//! scrolls up a line when writing past the bottom row.  Only the framebuffer is changed, so
+//! [`Terminal::show`] must be called to update the display.
+
+use super::{font::glyph, size::DisplaySize, size::DisplaySize128x64, P14};
+use crate::OutOfRange;
+use embedded_graphics_core::{
+    draw_target::DrawTarget, geometry::Point, pixelcolor::BinaryColor, Pixel,
+};
+use embedded_hal::i2c::I2c;
+
+const CELL_WIDTH: u8 = 6;
+const CELL_HEIGHT: u8 = 8;
+
+pub struct Terminal<I2C, S: DisplaySize = DisplaySize128x64> {
+    display: P14<I2C, S>,
+    column: u8,
+    row: u8,
+}
+
+impl<I2C: I2c, S: DisplaySize> Terminal<I2C, S> {
+    pub const COLUMNS: u8 = S::WIDTH / CELL_WIDTH;
+    pub const ROWS: u8 = S::HEIGHT / CELL_HEIGHT;
+
+    /// Wraps `display`, starting with the cursor in the top left corner.  The framebuffer is left
+    /// as is.
+    pub const fn new(display: P14<I2C, S>) -> Self {
+        Self {
+            display,
+            column: 0,
+            row: 0,
+        }
+    }
+
+    pub fn release(self) -> P14<I2C, S> {
+        self.display
+    }
+
+    /// Gives access to the wrapped display, e.g. for drawing graphics alongside text.
+    pub const fn display(&mut self) -> &mut P14<I2C, S> {
+        &mut self.display
+    }
+
+    /// The current (column, row) of the cursor.
+    pub const fn cursor(&self) -> (u8, u8) {
+        (self.column, self.row)
+    }
+
+    pub const fn set_cursor(&mut self, column: u8, row: u8) -> Result<(), OutOfRange> {
+        if column >= Self::COLUMNS || row >= Self::ROWS {
+            return Err(OutOfRange);
+        }
+        self.column = column;
+        self.row = row;
+        Ok(())
+    }
+
+    /// Blanks the framebuffer and returns the cursor to the top left corner.
+    pub fn clear(&mut self) {
+        self.display.framebuffer.as_mut().fill(0);
+        self.display.dirty = P14::<I2C, S>::ALL_DIRTY;
+        self.column = 0;
+        self.row = 0;
+    }
+
+    pub fn show(&mut self) -> Result<(), I2C::Error> {
+        self.display.show()
+    }
+
+    fn new_line(&mut self) {
+        self.column = 0;
+        if self.row + 1 < Self::ROWS {
+            self.row += 1;
+        } else {
+            let line = usize::from(S::WIDTH);
+            let framebuffer = self.display.framebuffer.as_mut();
+            framebuffer.copy_within(line.., 0);
+            let length = framebuffer.len();
+            framebuffer[length - line..].fill(0);
+            self.display.dirty = P14::<I2C, S>::ALL_DIRTY;
+        }
+    }
+
+    fn put(&mut self, character: char) {
+        if self.column >= Self::COLUMNS {
+            self.new_line();
+        }
+        let left = i32::from(self.column * CELL_WIDTH);
+        let top = i32::from(self.row * CELL_HEIGHT);
+        let glyph = glyph(character);
+        let pixels = (0..CELL_WIDTH).flat_map(|x| {
+            let bits = glyph.get(usize::from(x)).copied().unwrap_or(0);
+            (0..CELL_HEIGHT).map(move |y| {
+                Pixel(
+                    Point::new(left + i32::from(x), top + i32::from(y)),
+                    BinaryColor::from(bits & (1 << y) != 0),
+                )
+            })
+        });
+        let Ok(()) = self.display.draw_iter(pixels);
+        self.column += 1;
+    }
+}
+
+impl<I2C: I2c, S: DisplaySize> core::fmt::Write for Terminal<I2C, S> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for character in s.chars() {
+            match character {
+                '\n' => self.new_line(),
+                '\r' => self.column = 0,
+                _ => self.put(character),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    extern crate std;
+    use core::fmt::Write;
+    use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+
+    use core::marker::PhantomData;
+
+    use crate::p14::{all_dirty, terminal::Terminal, CLEAN, P14};
+    use crate::OutOfRange;
+
+    const fn terminal(i2c: I2cMock) -> Terminal<I2cMock> {
+        Terminal::new(P14::<_> {
+            i2c,
+            address: 0x3C,
+            framebuffer: [0; 1024],
+            dirty: CLEAN,
+            size: PhantomData,
+        })
+    }
+
+    #[test]
+    pub fn write() {
+        let i2c = I2cMock::new(&[]);
+        let mut i2c_clone = i2c.clone();
+        let mut terminal = terminal(i2c);
+
+        write!(terminal, "Hi").unwrap();
+        assert_eq!(terminal.cursor(), (2, 0));
+        assert_eq!(
+            terminal.display.framebuffer[..12],
+            [0x7F, 0x08, 0x08, 0x08, 0x7F, 0x00, 0x00, 0x44, 0x7D, 0x40, 0x00, 0x00]
+        );
+        let mut expected = CLEAN;
+        expected[0] = Some((0, 9));
+        assert_eq!(terminal.display.dirty, expected);
+
+        writeln!(terminal).unwrap();
+        write!(terminal, "!").unwrap();
+        assert_eq!(terminal.cursor(), (1, 1));
+        assert_eq!(terminal.display.framebuffer[128..131], [0x00, 0x00, 0x5F]);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn wrap() {
+        let i2c = I2cMock::new(&[]);
+        let mut i2c_clone = i2c.clone();
+        let mut terminal = terminal(i2c);
+
+        write!(terminal, "{}", "-".repeat(21)).unwrap();
+        assert_eq!(terminal.cursor(), (21, 0));
+        writeln!(terminal).unwrap();
+        assert_eq!(terminal.cursor(), (0, 1));
+        write!(terminal, "{}", "-".repeat(22)).unwrap();
+        assert_eq!(terminal.cursor(), (1, 2));
+        assert_eq!(terminal.display.framebuffer[256], 0x08);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn scroll() {
+        let i2c = I2cMock::new(&[]);
+        let mut i2c_clone = i2c.clone();
+        let mut terminal = terminal(i2c);
+
+        write!(terminal, "|\n\n\n\n\n\n\n").unwrap();
+        assert_eq!(terminal.cursor(), (0, 7));
+        terminal.display.dirty = CLEAN;
+        writeln!(terminal).unwrap();
+        assert_eq!(terminal.cursor(), (0, 7));
+        assert_eq!(terminal.display.framebuffer[2], 0x00);
+        assert_eq!(terminal.display.dirty, all_dirty(128, 8));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_cursor() {
+        let i2c = I2cMock::new(&[]);
+        let mut i2c_clone = i2c.clone();
+        let mut terminal = terminal(i2c);
+
+        terminal.set_cursor(20, 7).unwrap();
+        write!(terminal, "|").unwrap();
+        assert_eq!(terminal.display.framebuffer[896 + 122], 0x7F);
+        assert!(terminal.set_cursor(21, 0).is_err());
+        assert!(matches!(terminal.set_cursor(0, 8), Err(OutOfRange)));
+
+        terminal.clear();
+        assert_eq!(terminal.cursor(), (0, 0));
+        assert_eq!(terminal.display.framebuffer, [0; 1024]);
+        i2c_clone.done();
+    }
+}
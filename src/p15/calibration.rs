@@ -0,0 +1,143 @@
+//! Hard and soft iron calibration for [`P15`](super::P15).
+//!
+//! Hard iron distortion (from magnetised material near the sensor) shifts every reading by a
+//! constant offset.  Soft iron distortion stretches the field unevenly along each axis.  Both are
+//! estimated from the minimum and maximum seen on each axis while the sensor is rotated through
+//! every orientation.
+
+/// Offsets are subtracted from each axis (in microtesla) before scaling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Calibration {
+    pub offset: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            offset: (0.0, 0.0, 0.0),
+            scale: (1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Calibration {
+    #[must_use]
+    pub fn apply(self, reading: (f32, f32, f32)) -> (f32, f32, f32) {
+        (
+            (reading.0 - self.offset.0) * self.scale.0,
+            (reading.1 - self.offset.1) * self.scale.1,
+            (reading.2 - self.offset.2) * self.scale.2,
+        )
+    }
+}
+
+/// Accumulates the extent of uncalibrated readings on each axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Calibrator {
+    min: [f32; 3],
+    max: [f32; 3],
+    samples: u32,
+}
+
+impl Default for Calibrator {
+    fn default() -> Self {
+        Self {
+            min: [f32::INFINITY; 3],
+            max: [f32::NEG_INFINITY; 3],
+            samples: 0,
+        }
+    }
+}
+
+impl Calibrator {
+    pub fn update(&mut self, reading: (f32, f32, f32)) {
+        for ((min, max), value) in self
+            .min
+            .iter_mut()
+            .zip(self.max.iter_mut())
+            .zip([reading.0, reading.1, reading.2])
+        {
+            *min = min.min(value);
+            *max = max.max(value);
+        }
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    #[must_use]
+    pub const fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Centres each axis on the midpoint of its extent and scales it so every axis has the mean
+    /// radius.  An axis that has not moved is left unscaled.
+    #[must_use]
+    pub fn calibration(&self) -> Calibration {
+        if self.samples == 0 {
+            return Calibration::default();
+        }
+        let offset = |axis: usize| f32::midpoint(self.max[axis], self.min[axis]);
+        let radius = |axis: usize| (self.max[axis] - self.min[axis]) / 2.0;
+        let (total, moved) = (0..3)
+            .map(radius)
+            .filter(|r| *r > 0.0)
+            .fold((0.0, 0.0), |(total, moved), r| (total + r, moved + 1.0));
+        let scale = |axis: usize| {
+            if radius(axis) > 0.0 {
+                total / moved / radius(axis)
+            } else {
+                1.0
+            }
+        };
+        Calibration {
+            offset: (offset(0), offset(1), offset(2)),
+            scale: (scale(0), scale(1), scale(2)),
+        }
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    use crate::p15::calibration::{Calibration, Calibrator};
+
+    #[test]
+    pub fn empty() {
+        let calibrator = Calibrator::default();
+        assert_eq!(calibrator.samples(), 0);
+        assert_eq!(calibrator.calibration(), Calibration::default());
+    }
+
+    #[test]
+    pub fn hard_and_soft_iron() {
+        let mut calibrator = Calibrator::default();
+        calibrator.update((0.0, 0.0, -60.0));
+        calibrator.update((20.0, 40.0, 60.0));
+        calibrator.update((10.0, 20.0, 0.0));
+        assert_eq!(calibrator.samples(), 3);
+
+        let calibration = calibrator.calibration();
+        assert_eq!(
+            calibration,
+            Calibration {
+                offset: (10.0, 20.0, 0.0),
+                scale: (3.0, 1.5, 0.5),
+            }
+        );
+        assert_eq!(calibration.apply((20.0, 40.0, 60.0)), (30.0, 30.0, 30.0));
+    }
+
+    #[test]
+    pub fn stationary_axis() {
+        let mut calibrator = Calibrator::default();
+        calibrator.update((-20.0, -40.0, 7.0));
+        calibrator.update((20.0, 40.0, 7.0));
+
+        assert_eq!(
+            calibrator.calibration(),
+            Calibration {
+                offset: (0.0, 0.0, 7.0),
+                scale: (1.5, 0.75, 1.0),
+            }
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Tilt compensated compass combining [`P15`] with a [`P26`] accelerometer.
+//!
+//! The accelerometer measures which way is down, so the magnetic field can be rotated back into
+//! the horizontal plane before the heading is taken.  Both boards are assumed to be mounted in
+//! the same orientation, flat side up.  When level, [`Compass::read`] gives the same heading as
+//! [`P15::heading`].
+
+use super::{normalise, P15};
+use crate::{p26::P26, OutOfRange};
+use embedded_hal::i2c::I2c;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<M, A> {
+    Magnetometer(M),
+    Accelerometer(A),
+}
+
+/// All angles are in degrees.  `heading` is in the range `0.0..360.0`, `pitch` in
+/// `-90.0..=90.0` and `roll` in `-180.0..=180.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orientation {
+    pub heading: f32,
+    pub pitch: f32,
+    pub roll: f32,
+}
+
+/// Wraps `angle` into `-180.0..180.0`.
+fn wrap(angle: f32) -> f32 {
+    let wrapped = (angle + 180.0) % 360.0;
+    if wrapped < 0.0 {
+        wrapped + 180.0
+    } else {
+        wrapped - 180.0
+    }
+}
+
+fn blend(previous: f32, measured: f32, weight: f32) -> f32 {
+    previous + wrap(measured - previous) * (1.0 - weight)
+}
+
+/// Tilt compensated orientation from a magnetic field reading and an acceleration reading that
+/// is dominated by gravity.  Units only need to be consistent per sensor.
+#[must_use]
+pub fn orientation(
+    magnetic: (f32, f32, f32),
+    acceleration: (f32, f32, f32),
+    declination: f32,
+) -> Orientation {
+    let (mx, my, mz) = magnetic;
+    let (ax, ay, az) = acceleration;
+    let roll = libm::atan2f(ay, az);
+    let (roll_sin, roll_cos) = libm::sincosf(roll);
+    let pitch = libm::atan2f(-ax, ay * roll_sin + az * roll_cos);
+    let (pitch_sin, pitch_cos) = libm::sincosf(pitch);
+    let forward = mx * pitch_cos + my * pitch_sin * roll_sin + mz * pitch_sin * roll_cos;
+    let side = mz * roll_sin - my * roll_cos;
+    Orientation {
+        heading: normalise(libm::atan2f(forward, side).to_degrees() + declination),
+        pitch: pitch.to_degrees(),
+        roll: roll.to_degrees(),
+    }
+}
+
+pub struct Compass<M, A> {
+    magnetometer: P15<M>,
+    accelerometer: P26<A>,
+    smoothing: Option<f32>,
+    estimate: Option<Orientation>,
+}
+
+impl<M: I2c, A: I2c> Compass<M, A> {
+    /// Both devices should already be initialised.  The magnetometer's calibration and
+    /// declination are used as is.
+    pub const fn new(magnetometer: P15<M>, accelerometer: P26<A>) -> Self {
+        Self {
+            magnetometer,
+            accelerometer,
+            smoothing: None,
+            estimate: None,
+        }
+    }
+
+    pub fn release(self) -> (P15<M>, P26<A>) {
+        (self.magnetometer, self.accelerometer)
+    }
+
+    pub const fn magnetometer(&mut self) -> &mut P15<M> {
+        &mut self.magnetometer
+    }
+
+    pub const fn accelerometer(&mut self) -> &mut P26<A> {
+        &mut self.accelerometer
+    }
+
+    /// Smooths readings with an exponential moving average that keeps `weight` of the previous
+    /// estimate and takes the rest from each new reading, reducing vibration at the cost of lag.
+    /// `None` disables smoothing.  `weight` must be in `0.0..1.0`.
+    pub fn set_smoothing(&mut self, weight: Option<f32>) -> Result<(), OutOfRange> {
+        if weight.is_some_and(|w| !(0.0..1.0).contains(&w)) {
+            return Err(OutOfRange);
+        }
+        self.smoothing = weight;
+        self.estimate = None;
+        Ok(())
+    }
+
+    pub fn read(&mut self) -> Result<Orientation, Error<M::Error, A::Error>> {
+        let magnetic = self.magnetometer.read().map_err(Error::Magnetometer)?;
+        let (x, y, z) = self
+            .accelerometer
+            .acceleration()
+            .map_err(Error::Accelerometer)?;
+        let measured = orientation(magnetic, (x, y, z), self.magnetometer.declination);
+        let estimate = match (self.smoothing, self.estimate) {
+            (Some(weight), Some(previous)) => Orientation {
+                heading: normalise(blend(previous.heading, measured.heading, weight)),
+                pitch: blend(previous.pitch, measured.pitch, weight),
+                roll: wrap(blend(previous.roll, measured.roll, weight)),
+            },
+            _ => measured,
+        };
+        self.estimate = Some(estimate);
+        Ok(estimate)
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::vec;
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    use core::f32::consts::FRAC_1_SQRT_2;
+
+    use crate::p15::compass::{orientation, Compass, Orientation};
+    use crate::{p15::P15, p26::P26, Driver};
+
+    fn assert_close(actual: Orientation, expected: Orientation) {
+        assert!(
+            (actual.heading - expected.heading).abs() < 1e-3,
+            "{actual:?}"
+        );
+        assert!((actual.pitch - expected.pitch).abs() < 1e-3, "{actual:?}");
+        assert!((actual.roll - expected.roll).abs() < 1e-3, "{actual:?}");
+    }
+
+    #[test]
+    pub fn level() {
+        assert_close(
+            orientation((27.2, -54.5, 81.6), (0.0, 0.0, 1.0), 0.0),
+            Orientation {
+                heading: 26.522_984,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+        assert_close(
+            orientation((27.2, -54.5, 81.6), (0.0, 0.0, 1.0), -30.0),
+            Orientation {
+                heading: 356.522_98,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+    }
+
+    #[test]
+    pub fn pitched() {
+        assert_close(
+            orientation(
+                (-17.244_11, -54.5, 84.267_67),
+                (-0.5, 0.0, 0.866_025_4),
+                0.0,
+            ),
+            Orientation {
+                heading: 26.522_984,
+                pitch: 30.0,
+                roll: 0.0,
+            },
+        );
+    }
+
+    #[test]
+    pub fn rolled() {
+        assert_close(
+            orientation(
+                (27.2, -96.237_23, 19.162_594),
+                (0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+                0.0,
+            ),
+            Orientation {
+                heading: 26.522_984,
+                pitch: 0.0,
+                roll: -45.0,
+            },
+        );
+    }
+
+    fn compass(expectations: &[I2cTransaction]) -> (Compass<I2cMock, I2cMock>, I2cMock) {
+        let i2c = I2cMock::new(expectations);
+        let i2c_clone = i2c.clone();
+        (
+            Compass::new(
+                P15::new(i2c.clone(), 0x1C).unwrap(),
+                P26::new(i2c, 0x19).unwrap(),
+            ),
+            i2c_clone,
+        )
+    }
+
+    fn reading(x: [u8; 2], y: [u8; 2]) -> [I2cTransaction; 4] {
+        [
+            I2cTransaction::write_read(0x1C, vec![0x01], x.to_vec()),
+            I2cTransaction::write_read(0x1C, vec![0x03], y.to_vec()),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x00, 0x00]),
+            I2cTransaction::write_read(0x19, vec![0xA8], vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x40]),
+        ]
+    }
+
+    #[test]
+    pub fn read() {
+        let (mut compass, mut i2c) = compass(&reading([0x64, 0x00], [0x00, 0x00]));
+
+        assert_close(
+            compass.read().unwrap(),
+            Orientation {
+                heading: 90.0,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+        i2c.done();
+    }
+
+    #[test]
+    pub fn smoothing() {
+        let expectations = [
+            reading([0x00, 0x00], [0x9C, 0xFF]),
+            reading([0x9C, 0xFF], [0x00, 0x00]),
+            reading([0x9C, 0xFF], [0x00, 0x00]),
+        ]
+        .concat();
+        let (mut compass, mut i2c) = compass(&expectations);
+
+        assert!(compass.set_smoothing(Some(1.0)).is_err());
+        compass.set_smoothing(Some(0.5)).unwrap();
+        assert_close(
+            compass.read().unwrap(),
+            Orientation {
+                heading: 0.0,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+        assert_close(
+            compass.read().unwrap(),
+            Orientation {
+                heading: 315.0,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+        assert_close(
+            compass.read().unwrap(),
+            Orientation {
+                heading: 292.5,
+                pitch: 0.0,
+                roll: 0.0,
+            },
+        );
+        i2c.done();
+    }
+}
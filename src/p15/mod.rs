@@ -13,8 +13,9 @@
 //! [Datasheet]: https://datasheet.lcsc.com/lcsc/2007101835_QST-QMC6310U_C669299.pdf
 
 use crate::Driver;
+use calibration::Calibration;
 use embedded_hal::i2c::I2c;
-use rust_decimal::prelude::*;
+use num_enum::IntoPrimitive;
 
 const REG_XOUT: u8 = 0x01;
 const REG_YOUT: u8 = 0x03;
@@ -25,22 +26,71 @@ const REG_CONTROL2: u8 = 0x0B;
 const REG_SIGN: u8 = 0x29;
 
 const CONTROL1_VALUE: u8 = 0b1100_1101;
-const CONTROL2_VALUE: u8 = 0b0000_0000;
 const SIGN_VALUE: u8 = 0b0000_0110;
 
+/// Full scale range of the magnetometer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum Range {
+    Gauss30 = 0b0000_0000,
+    Gauss12 = 0b0000_0100,
+    Gauss8 = 0b0000_1000,
+    Gauss2 = 0b0000_1100,
+}
+
+impl Range {
+    const fn lsb_per_microtesla(self) -> f32 {
+        match self {
+            Self::Gauss30 => 10.0,
+            Self::Gauss12 => 25.0,
+            Self::Gauss8 => 37.5,
+            Self::Gauss2 => 150.0,
+        }
+    }
+}
+
+/// Output data rate in normal mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum DataRate {
+    Hz10 = 0b0000_0000,
+    Hz50 = 0b0000_0100,
+    Hz100 = 0b0000_1000,
+    Hz200 = 0b0000_1100,
+}
+
+/// Wraps a heading in degrees into `0.0..360.0`.
+fn normalise(heading: f32) -> f32 {
+    let wrapped = heading % 360.0;
+    if wrapped < 0.0 {
+        wrapped + 360.0
+    } else {
+        wrapped
+    }
+}
+
 pub struct P15<I2C> {
     i2c: I2C,
     address: u8,
+    range: Range,
+    calibration: Calibration,
+    declination: f32,
 }
 
 impl<I2C: I2c> Driver<I2C, I2C::Error> for P15<I2C> {
     fn new_inner(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            range: Range::Gauss30,
+            calibration: Calibration::default(),
+            declination: 0.0,
+        }
     }
 
     fn init_inner(mut self) -> Result<Self, I2C::Error> {
         self.set_sign()?;
-        self.set_range()?;
+        self.set_range(Range::Gauss30)?;
         self.set_control_register()?;
         Ok(self)
     }
@@ -54,9 +104,34 @@ impl<I2C: I2c> P15<I2C> {
         Ok(())
     }
 
-    fn set_range(&mut self) -> Result<(), I2C::Error> {
+    pub fn set_range(&mut self, range: Range) -> Result<(), I2C::Error> {
+        self.i2c.write(
+            self.address,
+            &[
+                REG_CONTROL2,
+                <Range as core::convert::Into<u8>>::into(range),
+            ],
+        )?;
+        self.range = range;
+
+        Ok(())
+    }
+
+    pub const fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn set_data_rate(&mut self, rate: DataRate) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1] = [0];
         self.i2c
-            .write(self.address, &[REG_CONTROL2, CONTROL2_VALUE])?;
+            .write_read(self.address, &[REG_CONTROL1], &mut data)?;
+        self.i2c.write(
+            self.address,
+            &[
+                REG_CONTROL1,
+                (data[0] & 0b1111_0011) | <DataRate as core::convert::Into<u8>>::into(rate),
+            ],
+        )?;
 
         Ok(())
     }
@@ -78,7 +153,8 @@ impl<I2C: I2c> P15<I2C> {
         }
     }
 
-    pub fn read(&mut self) -> Result<(Decimal, Decimal, Decimal), I2C::Error> {
+    /// The raw (x, y, z) counts as reported by the sensor.
+    pub fn read_raw(&mut self) -> Result<(i16, i16, i16), I2C::Error> {
         let mut data_x: [u8; 2] = [0, 0];
         self.i2c
             .write_read(self.address, &[REG_XOUT], &mut data_x)?;
@@ -89,43 +165,105 @@ impl<I2C: I2c> P15<I2C> {
         self.i2c
             .write_read(self.address, &[REG_ZOUT], &mut data_z)?;
         Ok((
-            Decimal::new(i16::from_le_bytes(data_x).into(), 1),
-            Decimal::new(i16::from_le_bytes(data_y).into(), 1),
-            Decimal::new(i16::from_le_bytes(data_z).into(), 1),
+            i16::from_le_bytes(data_x),
+            i16::from_le_bytes(data_y),
+            i16::from_le_bytes(data_z),
+        ))
+    }
+
+    fn read_uncalibrated(&mut self) -> Result<(f32, f32, f32), I2C::Error> {
+        let (x, y, z) = self.read_raw()?;
+        let scale = self.range.lsb_per_microtesla();
+        Ok((
+            f32::from(x) / scale,
+            f32::from(y) / scale,
+            f32::from(z) / scale,
         ))
     }
 
-    // should be moved to separate impl
-    // pub fn read_angle(&mut self) -> Result<Angle, I2C::Error> {
-    //     let (x, y, _) = self.read()?;
-    //     Ok(Angle::from_radians(atan2(x, -y)))
-    // }
+    /// The field strength along each axis in microtesla, with the current [`Calibration`]
+    /// applied.
+    pub fn read(&mut self) -> Result<(f32, f32, f32), I2C::Error> {
+        Ok(self.calibration.apply(self.read_uncalibrated()?))
+    }
+
+    pub const fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    pub const fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    /// Collects `samples` readings into a [`calibration::Calibrator`] and applies the resulting
+    /// [`Calibration`].  The sensor should be rotated through every orientation while this runs.
+    /// Blocks until enough samples are ready.
+    pub fn calibrate(&mut self, samples: u16) -> Result<Calibration, I2C::Error> {
+        let mut calibrator = calibration::Calibrator::default();
+        let mut remaining = samples;
+        while remaining > 0 {
+            if self.data_ready()? {
+                calibrator.update(self.read_uncalibrated()?);
+                remaining -= 1;
+            }
+        }
+        self.calibration = calibrator.calibration();
+        Ok(self.calibration)
+    }
 
-    // pub fn read_magnitude(&mut self) -> Result<f64, I2C::Error> {
-    //     let (x, y, z) = self.read()?;
-    //     Ok(sqrt(x * x + y * y + z * z))
-    // }
+    /// Sets the magnetic declination in degrees (east positive) that is added to
+    /// [`P15::heading`] to give a heading relative to true north.
+    pub const fn set_declination(&mut self, degrees: f32) {
+        self.declination = degrees;
+    }
+
+    /// The compass heading in degrees in the range `0.0..360.0`.  Only meaningful while the
+    /// sensor is held level.
+    pub fn heading(&mut self) -> Result<f32, I2C::Error> {
+        let (x, y, _) = self.read()?;
+        Ok(normalise(
+            libm::atan2f(x, -y).to_degrees() + self.declination,
+        ))
+    }
+
+    /// The total field strength in microtesla.
+    pub fn magnitude(&mut self) -> Result<f32, I2C::Error> {
+        let (x, y, z) = self.read()?;
+        Ok(libm::hypotf(libm::hypotf(x, y), z))
+    }
 }
 
 #[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
 mod test {
-    use rust_decimal::prelude::*;
     extern crate std;
     use std::vec;
     extern crate embedded_hal;
     extern crate embedded_hal_mock;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p15::P15;
+    use crate::p15::{calibration::Calibration, DataRate, Range, P15};
     use crate::Driver;
 
+    const fn p15(i2c: I2cMock) -> P15<I2cMock> {
+        P15 {
+            i2c,
+            address: 0x1C,
+            range: Range::Gauss30,
+            calibration: Calibration {
+                offset: (0.0, 0.0, 0.0),
+                scale: (1.0, 1.0, 1.0),
+            },
+            declination: 0.0,
+        }
+    }
+
     #[test]
     pub fn set_control_register() {
         let expectations = [I2cTransaction::write(0x1C, vec![0x0A, 0b1100_1101])];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
 
         assert_eq!(p15.set_control_register(), Ok(()));
         i2c_clone.done();
@@ -137,7 +275,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
 
         assert_eq!(p15.set_sign(), Ok(()));
         i2c_clone.done();
@@ -149,9 +287,9 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
 
-        assert_eq!(p15.set_range(), Ok(()));
+        assert_eq!(p15.set_range(Range::Gauss30), Ok(()));
         i2c_clone.done();
     }
 
@@ -176,7 +314,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
         assert_eq!(p15.data_ready(), Ok(true));
 
         i2c_clone.done();
@@ -188,7 +326,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
         assert_eq!(p15.data_ready(), Ok(false));
 
         i2c_clone.done();
@@ -200,7 +338,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
+        let mut p15 = p15(i2c);
         assert_eq!(p15.data_ready(), Ok(false));
 
         i2c_clone.done();
@@ -216,51 +354,150 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p15 = P15 { i2c, address: 0x1C };
-        assert_eq!(
-            p15.read(),
-            Ok((
-                Decimal::new(272, 1),
-                Decimal::new(-545, 1),
-                Decimal::new(816, 1),
-            ))
-        );
+        let mut p15 = p15(i2c);
+        assert_eq!(p15.read(), Ok((27.2, -54.5, 81.6)));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_range_gauss2() {
+        let expectations = [
+            I2cTransaction::write(0x1C, vec![0x0B, 0x0C]),
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x2C, 0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0x6A, 0xFF]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x00, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        assert_eq!(p15.set_range(Range::Gauss2), Ok(()));
+        assert_eq!(p15.range(), Range::Gauss2);
+        assert_eq!(p15.read(), Ok((2.0, -1.0, 0.0)));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_data_rate() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x0A], vec![0b1100_1101]),
+            I2cTransaction::write(0x1C, vec![0x0A, 0b1100_0101]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        assert_eq!(p15.set_data_rate(DataRate::Hz50), Ok(()));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_raw() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        assert_eq!(p15.read_raw(), Ok((272, -545, 816)));
 
         i2c_clone.done();
     }
 
-    // #[test]
-    // pub fn read_angle() {
-    //     let expectations = [
-    //         I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
-    //         I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
-    //         I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
-    //     ];
-    //     let i2c = I2cMock::new(&expectations);
-    //     let mut i2c_clone = i2c.clone();
-
-    //     let mut p15 = P15 { i2c, address: 0x1C };
-    //     assert_eq!(
-    //         p15.read_angle(),
-    //         Ok(Angle::from_degrees(26.522_983_798_797_82))
-    //     );
-
-    //     i2c_clone.done();
-    // }
-
-    // #[test]
-    // pub fn read_magnitude() {
-    //     let expectations = [
-    //         I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
-    //         I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
-    //         I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
-    //     ];
-    //     let i2c = I2cMock::new(&expectations);
-    //     let mut i2c_clone = i2c.clone();
-
-    //     let mut p15 = P15 { i2c, address: 0x1C };
-    //     assert_eq!(p15.read_magnitude(), Ok(101.826_568_242_281_45_f64));
-
-    //     i2c_clone.done();
-    // }
+    #[test]
+    pub fn read_calibrated() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x64, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0x9C, 0xFF]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x00, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        p15.set_calibration(Calibration {
+            offset: (5.0, -5.0, 0.0),
+            scale: (2.0, 0.5, 1.0),
+        });
+        assert_eq!(p15.read(), Ok((10.0, -2.5, 0.0)));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn calibrate() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x09], vec![0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x09], vec![0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0xC8, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0x64, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x64, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x09], vec![0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x00, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0x9C, 0xFF]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x44, 0xFD]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        let expected = Calibration {
+            offset: (10.0, 0.0, -30.0),
+            scale: (2.0, 2.0, 0.5),
+        };
+        assert_eq!(p15.calibrate(2), Ok(expected));
+        assert_eq!(p15.calibration(), expected);
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn heading() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x00, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0x64, 0x00]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x00, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        assert!((p15.heading().unwrap() - 26.522_984).abs() < 1e-4);
+        p15.set_declination(-30.0);
+        assert!((p15.heading().unwrap() - 356.522_98).abs() < 1e-3);
+        assert!((p15.heading().unwrap() - 150.0).abs() < 1e-4);
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn magnitude() {
+        let expectations = [
+            I2cTransaction::write_read(0x1C, vec![0x01], vec![0x10, 0x01]),
+            I2cTransaction::write_read(0x1C, vec![0x03], vec![0xDF, 0xFD]),
+            I2cTransaction::write_read(0x1C, vec![0x05], vec![0x30, 0x03]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p15 = p15(i2c);
+        assert!((p15.magnitude().unwrap() - 101.826_57).abs() < 1e-3);
+
+        i2c_clone.done();
+    }
 }
+
+pub mod calibration;
+pub mod compass;
@@ -0,0 +1,233 @@
+//! Shares the I2C bus between short lived [`Mfrc522`] handles and direct register access, for the
+//! MIFARE Classic commands the mfrc522 driver does not send.
+
+use core::cell::Cell;
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+use mfrc522::comm::blocking::i2c::I2cInterface;
+use mfrc522::error::Error;
+use mfrc522::{Initialized, Mfrc522};
+
+const REG_COMMAND: u8 = 0x01;
+const REG_COM_IRQ: u8 = 0x04;
+const REG_ERROR: u8 = 0x06;
+const REG_FIFO_DATA: u8 = 0x09;
+const REG_FIFO_LEVEL: u8 = 0x0A;
+const REG_CONTROL: u8 = 0x0C;
+const REG_BIT_FRAMING: u8 = 0x0D;
+
+const COMMAND_IDLE: u8 = 0x00;
+const COMMAND_TRANSCEIVE: u8 = 0x0C;
+const COMMAND_MF_AUTHENT: u8 = 0x0E;
+
+const IRQ_TIMER: u8 = 0x01;
+const IRQ_ERROR: u8 = 0x02;
+const IRQ_IDLE: u8 = 0x10;
+const IRQ_RX: u8 = 0x20;
+const IRQ_ALL: u8 = 0x7F;
+const FIFO_FLUSH: u8 = 0x80;
+const START_SEND: u8 = 0x80;
+const LAST_BITS: u8 = 0x07;
+
+const ERROR_PROTOCOL: u8 = 0x01;
+const ERROR_PARITY: u8 = 0x02;
+const ERROR_CRC: u8 = 0x04;
+const ERROR_COLLISION: u8 = 0x08;
+const ERROR_BUFFER_OVERFLOW: u8 = 0x10;
+const ERROR_TEMPERATURE: u8 = 0x40;
+const ERROR_WRITE: u8 = 0x80;
+
+/// The 4 bit acknowledge of a MIFARE Classic tag.
+const ACK: u8 = 0x0A;
+/// The longest frame sent here, a value operand, plus its CRC.
+const MAX_FRAME: usize = 6;
+
+pub type Handle<'a, I2C> = Mfrc522<I2cInterface<Lent<'a, I2C>>, Initialized>;
+
+/// Lends the bus to a [`Mfrc522`] handle.  While `skip` is set transactions are dropped and reads
+/// return zeros, so that the handle can be initialized without resetting the MFRC522.
+pub struct Lent<'a, I2C> {
+    i2c: &'a mut I2C,
+    skip: &'a Cell<bool>,
+}
+
+impl<I2C: I2c> ErrorType for Lent<'_, I2C> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: I2c> I2c for Lent<'_, I2C> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if !self.skip.get() {
+            return self.i2c.transaction(address, operations);
+        }
+        for operation in operations {
+            if let Operation::Read(buffer) = operation {
+                buffer.fill(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `operation` with a handle for an MFRC522 that has already been initialized.
+pub fn with_handle<I2C, T, F>(
+    i2c: &mut I2C,
+    address: u8,
+    operation: F,
+) -> Result<T, Error<I2C::Error>>
+where
+    I2C: I2c,
+    F: FnOnce(&mut Handle<'_, I2C>) -> Result<T, Error<I2C::Error>>,
+{
+    let skip = Cell::new(true);
+    let mut handle = Mfrc522::new(I2cInterface::new(Lent { i2c, skip: &skip }, address)).init()?;
+    skip.set(false);
+    operation(&mut handle)
+}
+
+/// CRC_A as defined by ISO 14443-3, least significant byte first.
+pub fn crc_a(data: &[u8]) -> [u8; 2] {
+    let mut crc: u16 = 0x6363;
+    for &byte in data {
+        let mut ch = byte ^ crc.to_le_bytes()[0];
+        ch ^= ch << 4_u8;
+        crc = (crc >> 8_u8)
+            ^ (u16::from(ch) << 8_u8)
+            ^ (u16::from(ch) << 3_u8)
+            ^ (u16::from(ch) >> 4_u8);
+    }
+    crc.to_le_bytes()
+}
+
+/// Direct access to the MFRC522 registers.  Expects the timer to be set up as the mfrc522 driver
+/// does, so that a tag that does not answer raises the timer interrupt.
+pub struct Registers<'a, I2C> {
+    pub i2c: &'a mut I2C,
+    pub address: u8,
+}
+
+impl<I2C: I2c> Registers<'_, I2C> {
+    fn read(&mut self, register: u8) -> Result<u8, Error<I2C::Error>> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[register], &mut data)
+            .map_err(Error::Comm)?;
+        Ok(data[0])
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write(self.address, &[register, value])
+            .map_err(Error::Comm)
+    }
+
+    fn check_error(&mut self) -> Result<(), Error<I2C::Error>> {
+        let error = self.read(REG_ERROR)?;
+        [
+            (ERROR_PROTOCOL, Error::Protocol),
+            (ERROR_PARITY, Error::Parity),
+            (ERROR_CRC, Error::Crc),
+            (ERROR_COLLISION, Error::Collision),
+            (ERROR_BUFFER_OVERFLOW, Error::BufferOverflow),
+            (ERROR_TEMPERATURE, Error::Overheating),
+            (ERROR_WRITE, Error::Wr),
+        ]
+        .into_iter()
+        .find(|(bit, _)| error & bit != 0)
+        .map_or(Ok(()), |(_, reported)| Err(reported))
+    }
+
+    /// Loads `data` into the FIFO and runs `command` until one of the `done` interrupts is raised.
+    fn execute(&mut self, command: u8, data: &[u8], done: u8) -> Result<(), Error<I2C::Error>> {
+        self.write(REG_COMMAND, COMMAND_IDLE)?;
+        self.write(REG_COM_IRQ, IRQ_ALL)?;
+        self.write(REG_FIFO_LEVEL, FIFO_FLUSH)?;
+        self.write(REG_BIT_FRAMING, 0)?;
+        self.i2c
+            .transaction(
+                self.address,
+                &mut [Operation::Write(&[REG_FIFO_DATA]), Operation::Write(data)],
+            )
+            .map_err(Error::Comm)?;
+        self.write(REG_COMMAND, command)?;
+        if command == COMMAND_TRANSCEIVE {
+            self.write(REG_BIT_FRAMING, START_SEND)?;
+        }
+        loop {
+            let irq = self.read(REG_COM_IRQ)?;
+            if irq & done != 0 {
+                break;
+            }
+            if irq & IRQ_TIMER != 0 {
+                return Err(Error::Timeout);
+            }
+        }
+        self.check_error()
+    }
+
+    /// Three pass authentication of the sector containing `block`, where `command` selects key A
+    /// or key B and `uid` holds the UID bytes the tag expects.
+    pub fn authenticate(
+        &mut self,
+        command: u8,
+        block: u8,
+        key: [u8; 6],
+        uid: [u8; 4],
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut frame = [0; 12];
+        frame[0] = command;
+        frame[1] = block;
+        frame[2..8].copy_from_slice(&key);
+        frame[8..].copy_from_slice(&uid);
+        self.execute(COMMAND_MF_AUTHENT, &frame, IRQ_ERROR | IRQ_IDLE)
+    }
+
+    /// Sends `data`, of up to 4 bytes, followed by its CRC.
+    fn send(&mut self, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        let mut frame = [0; MAX_FRAME];
+        let length = data.len() + 2;
+        frame[..data.len()].copy_from_slice(data);
+        frame[data.len()..length].copy_from_slice(&crc_a(data));
+        self.execute(
+            COMMAND_TRANSCEIVE,
+            &frame[..length],
+            IRQ_RX | IRQ_ERROR | IRQ_IDLE,
+        )
+    }
+
+    /// Sends `data` and expects the tag to acknowledge it.
+    pub fn send_acknowledged(&mut self, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        self.send(data)?;
+        let level = self.read(REG_FIFO_LEVEL)?;
+        let bits = self.read(REG_CONTROL)? & LAST_BITS;
+        let response = self.read(REG_FIFO_DATA)?;
+        if level == 1 && bits == 4 && response & 0x0F == ACK {
+            Ok(())
+        } else {
+            Err(Error::Nak)
+        }
+    }
+
+    /// Sends `data` to a tag that only answers to report an error, as for the operand of an
+    /// increment or decrement.
+    pub fn send_unacknowledged(&mut self, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        match self.send(data) {
+            Err(Error::Timeout) => Ok(()),
+            Ok(()) => Err(Error::Nak),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    use crate::p16::bus::crc_a;
+
+    #[test]
+    pub fn crc() {
+        assert_eq!(crc_a(&[0x50, 0x00]), [0x57, 0xCD]);
+    }
+}
@@ -0,0 +1,247 @@
+//! Sector trailer access bits and value blocks for MIFARE Classic 1K tags.
+//!
+//! A 1K tag has 16 sectors of 4 blocks.  The last block of each sector is the trailer, holding
+//! key A, the access bits and key B.
+
+/// Factory default key for both key A and key B.
+pub const DEFAULT_KEY: [u8; 6] = [0xFF; 6];
+
+/// Which key of the sector trailer to authenticate with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    A,
+    B,
+}
+
+pub const BLOCKS_PER_SECTOR: u8 = 4;
+
+/// The trailer block of `sector`.
+#[must_use]
+pub const fn trailer_block(sector: u8) -> u8 {
+    sector * BLOCKS_PER_SECTOR + BLOCKS_PER_SECTOR - 1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidAccessBits;
+
+/// Which key may perform an operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    KeyAOrB,
+    KeyB,
+    Never,
+}
+
+/// Permissions for a data block given its access condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataBlockAccess {
+    pub read: Access,
+    pub write: Access,
+    pub increment: Access,
+    /// Also covers transfer and restore.
+    pub decrement: Access,
+}
+
+/// The access conditions (C1, C2 and C3 packed as `0bC1C2C3`) of the four blocks of a sector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessBits {
+    conditions: [u8; 4],
+}
+
+impl AccessBits {
+    /// Conditions as shipped from the factory: data blocks are open to either key and key A
+    /// can rewrite the trailer.
+    pub const TRANSPORT: Self = Self {
+        conditions: [0b000, 0b000, 0b000, 0b001],
+    };
+
+    #[must_use]
+    pub const fn new(conditions: [u8; 4]) -> Self {
+        Self {
+            conditions: [
+                conditions[0] & 0b111,
+                conditions[1] & 0b111,
+                conditions[2] & 0b111,
+                conditions[3] & 0b111,
+            ],
+        }
+    }
+
+    /// Parses bytes 6 to 8 of a sector trailer, checking the inverted copies of each bit.
+    pub fn from_bytes(bytes: [u8; 3]) -> Result<Self, InvalidAccessBits> {
+        let c1 = bytes[1] >> 4_u8;
+        let c2 = bytes[2] & 0x0F;
+        let c3 = bytes[2] >> 4_u8;
+        if bytes[0] & 0x0F != !c1 & 0x0F
+            || bytes[0] >> 4_u8 != !c2 & 0x0F
+            || bytes[1] & 0x0F != !c3 & 0x0F
+        {
+            return Err(InvalidAccessBits);
+        }
+        Ok(Self {
+            conditions: core::array::from_fn(|block| {
+                (((c1 >> block) & 1) << 2_u8) | (((c2 >> block) & 1) << 1_u8) | ((c3 >> block) & 1)
+            }),
+        })
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 3] {
+        let bits = |shift: u8| {
+            self.conditions
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (block, condition)| {
+                    bits | (((condition >> shift) & 1) << block)
+                })
+        };
+        let (c1, c2, c3) = (bits(2), bits(1), bits(0));
+        [
+            ((!c2 & 0x0F) << 4) | (!c1 & 0x0F),
+            (c1 << 4) | (!c3 & 0x0F),
+            (c3 << 4) | c2,
+        ]
+    }
+
+    /// The packed condition of `block` within the sector, where block 3 is the trailer.
+    #[must_use]
+    pub const fn condition(&self, block: usize) -> u8 {
+        self.conditions[block]
+    }
+
+    /// Permissions for data block 0, 1 or 2 of the sector.
+    #[must_use]
+    pub const fn data_block(&self, block: usize) -> DataBlockAccess {
+        use Access::{KeyAOrB, KeyB, Never};
+        let (read, write, increment, decrement) = match self.conditions[block] {
+            0b000 => (KeyAOrB, KeyAOrB, KeyAOrB, KeyAOrB),
+            0b010 => (KeyAOrB, Never, Never, Never),
+            0b100 => (KeyAOrB, KeyB, Never, Never),
+            0b110 => (KeyAOrB, KeyB, KeyB, KeyAOrB),
+            0b001 => (KeyAOrB, Never, Never, KeyAOrB),
+            0b011 => (KeyB, KeyB, Never, Never),
+            0b101 => (KeyB, Never, Never, Never),
+            _ => (Never, Never, Never, Never),
+        };
+        DataBlockAccess {
+            read,
+            write,
+            increment,
+            decrement,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidValueBlock;
+
+/// A signed value stored three times (once inverted) along with an address byte, as used by
+/// the increment and decrement commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueBlock {
+    pub value: i32,
+    /// Free for applications to use, conventionally the block number of a backup copy.
+    pub address: u8,
+}
+
+impl ValueBlock {
+    pub fn from_bytes(bytes: &[u8; 16]) -> Result<Self, InvalidValueBlock> {
+        let value = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let inverted = [bytes[4], bytes[5], bytes[6], bytes[7]].map(|byte| !byte);
+        let copy = [bytes[8], bytes[9], bytes[10], bytes[11]];
+        let address = bytes[12];
+        if value != inverted
+            || value != copy
+            || bytes[13] != !address
+            || bytes[14] != address
+            || bytes[15] != !address
+        {
+            return Err(InvalidValueBlock);
+        }
+        Ok(Self {
+            value: i32::from_le_bytes(value),
+            address,
+        })
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let value = self.value.to_le_bytes();
+        let mut bytes = [0; 16];
+        bytes[..4].copy_from_slice(&value);
+        bytes[4..8].copy_from_slice(&value.map(|byte| !byte));
+        bytes[8..12].copy_from_slice(&value);
+        bytes[12..].copy_from_slice(&[self.address, !self.address, self.address, !self.address]);
+        bytes
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    use crate::p16::classic::{
+        trailer_block, Access, AccessBits, DataBlockAccess, InvalidAccessBits, InvalidValueBlock,
+        ValueBlock,
+    };
+
+    #[test]
+    pub fn trailer() {
+        assert_eq!(trailer_block(0), 3);
+        assert_eq!(trailer_block(15), 63);
+    }
+
+    #[test]
+    pub fn transport_access_bits() {
+        assert_eq!(AccessBits::TRANSPORT.to_bytes(), [0xFF, 0x07, 0x80]);
+        assert_eq!(
+            AccessBits::from_bytes([0xFF, 0x07, 0x80]),
+            Ok(AccessBits::TRANSPORT)
+        );
+        assert_eq!(
+            AccessBits::TRANSPORT.data_block(0),
+            DataBlockAccess {
+                read: Access::KeyAOrB,
+                write: Access::KeyAOrB,
+                increment: Access::KeyAOrB,
+                decrement: Access::KeyAOrB,
+            }
+        );
+    }
+
+    #[test]
+    pub fn value_block_access_bits() {
+        let bits = AccessBits::new([0b110, 0b001, 0b100, 0b011]);
+        assert_eq!(bits.to_bytes(), [0x6A, 0x55, 0xA9]);
+        assert_eq!(AccessBits::from_bytes(bits.to_bytes()), Ok(bits));
+        assert_eq!(bits.condition(3), 0b011);
+        assert_eq!(bits.data_block(0).increment, Access::KeyB);
+        assert_eq!(bits.data_block(1).decrement, Access::KeyAOrB);
+        assert_eq!(bits.data_block(1).write, Access::Never);
+        assert_eq!(bits.data_block(2).write, Access::KeyB);
+        assert_eq!(
+            AccessBits::from_bytes([0xFF, 0x07, 0x81]),
+            Err(InvalidAccessBits)
+        );
+    }
+
+    #[test]
+    pub fn value_block() {
+        let block = ValueBlock {
+            value: -2,
+            address: 5,
+        };
+        let bytes = block.to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                0xFE, 0xFF, 0xFF, 0xFF, 0x01, 0x00, 0x00, 0x00, 0xFE, 0xFF, 0xFF, 0xFF, 0x05, 0xFA,
+                0x05, 0xFA
+            ]
+        );
+        assert_eq!(ValueBlock::from_bytes(&bytes), Ok(block));
+
+        let mut corrupt = bytes;
+        corrupt[9] = 0;
+        assert_eq!(ValueBlock::from_bytes(&corrupt), Err(InvalidValueBlock));
+        assert_eq!(ValueBlock::from_bytes(&[0; 16]), Err(InvalidValueBlock));
+    }
+}
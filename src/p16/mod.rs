@@ -1,213 +1,777 @@
-// //! # Unofficial Rust Driver for PiicoDev RFID Module
-// //!
-// //! ## External Links
-// //!
-// //! - [Official Hardware Repository]
-// //! - [Official MicroPython Repository]
-// //! - [Official Product Site]
-// //! - [Datasheet]
-// //!
-// //! [Official Hardware Repository]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-Module
-// //! [Official MicroPython Repository]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-MicroPython-Module
-// //! [Official Product Site]: https://piico.dev/p16
-// //! [Datasheet]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-Module/raw/main/Documents/MFRC522.pdf
-// use embedded_hal::i2c::I2c;
-// use mfrc522::comm::blocking::i2c::I2cInterface;
-// pub use mfrc522::{GenericUid, Uid};
-// use mfrc522::{Initialized, Mfrc522, State, Uninitialized};
-//
-// pub struct P16<I2C: I2c, S: State> {
-//     #[allow(dead_code)]
-//     mfrc522: Mfrc522<I2cInterface<I2C>, S>,
-// }
-//
-// use crate::Driver;
-//
-// impl<I2C: I2c> Driver<I2C> for P16<I2C, Uninitialized> {
-//     fn new_inner(i2c: I2C, address: u8) -> Self {
-//         Self {
-//             mfrc522: Mfrc522::new(I2cInterface::new(i2c, address)),
-//         }
-//     }
-// }
-//
-// impl<I2C: I2c> P16<I2C, Uninitialized> {
-//     fn init_inner(self) -> Result<P16<I2C, Initialized>, mfrc522::error::Error<I2C::Error>> {
-//         let res: P16<_, Initialized> = P16 {
-//             mfrc522: self.mfrc522.init_inner()?,
-//         };
-//         Ok(res)
-//     }
-// }
-//
-// impl<I2C: I2c> P16<I2C, Initialized> {
-//     pub fn read_tag_id(&mut self) -> Result<Uid, mfrc522::error::Error<I2C::Error>> {
-//         let atqa = self.mfrc522.reqa()?;
-//         let uid = self.mfrc522.select(&atqa)?;
-//         Ok(uid)
-//     }
-// }
-//
-// #[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
-// mod test {
-//     use crate::Driver;
-//     extern crate std;
-//     use std::vec;
-//     extern crate embedded_hal;
-//     extern crate embedded_hal_mock;
-//     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
-//
-//     use crate::p16::P16;
-//     use mfrc522::comm::blocking::i2c::I2cInterface;
-//     use mfrc522::Mfrc522;
-//
-//     #[test]
-//     pub fn new() {
-//         let expectations = [
-//             // reset
-//             I2cTransaction::write(0x2C, vec![0x01, 0x0F]),
-//             // wait for MFRC522 to be ready
-//             I2cTransaction::write_read(0x2C, vec![0x01], vec![0xff]),
-//             I2cTransaction::write_read(0x2C, vec![0x01], vec![0x00]),
-//             // TxModeReg
-//             I2cTransaction::write(0x2C, vec![0x12, 0x00]),
-//             // RxModeReg
-//             I2cTransaction::write(0x2C, vec![0x13, 0x00]),
-//             // Reset ModWidthReg to default value
-//             I2cTransaction::write(0x2C, vec![0x24, 0x26]),
-//             // Configure the timer, so we can get a timeout if something goes wrong
-//             // when communicating with a PICC:
-//             // - Set timer to start automatically at the end of the transmission
-//             I2cTransaction::write(0x2C, vec![0x2A, 0x80]),
-//             // - Configure the prescaler to determine the timer frequency:
-//             //   f_timer = 13.56 MHz / (2 * TPreScaler + 1)
-//             //   so for 40kHz frequency (25μs period), TPreScaler = 0x0A9
-//             I2cTransaction::write(0x2C, vec![0x2B, 0xA9]),
-//             // - Set the reload value to determine the timeout
-//             //   for a 25ms timeout, we need a value of 1000 = 0x3E8
-//             I2cTransaction::write(0x2C, vec![0x2C, 0x03]),
-//             I2cTransaction::write(0x2C, vec![0x2D, 0xE8]),
-//             I2cTransaction::write(0x2C, vec![0x15, 0x40]),
-//             // Set preset value of CRC coprocessor according to ISO 14443-3 part 6.2.4
-//             I2cTransaction::write(0x2C, vec![0x11, (0x3f & (!0b11)) | 0b01]),
-//             // Enable antenna
-//             I2cTransaction::write_read(0x2C, vec![0x14], vec![0x11]),
-//             I2cTransaction::write(0x2C, vec![0x14, 0x13]),
-//         ];
-//         let i2c = I2cMock::new(&expectations);
-//         let mut i2c_clone = i2c.clone();
-//
-//         P16::new(i2c, 0x2C).unwrap().init().unwrap();
-//
-//         i2c_clone.done();
-//     }
-//
-//     #[test]
-//     pub fn read_tag_id_ntag() {
-//         let expectations = [
-//             // reqa
-//             I2cTransaction::write(0x2C, vec![0x01, 0x0F]),
-//             I2cTransaction::write_read(0x2C, vec![0x01], vec![0xff]),
-//             I2cTransaction::write_read(0x2C, vec![0x01], vec![0x00]),
-//             I2cTransaction::write(0x2C, vec![0x12, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x13, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x24, 0x26]),
-//             I2cTransaction::write(0x2C, vec![0x2A, 0x80]),
-//             I2cTransaction::write(0x2C, vec![0x2B, 0xA9]),
-//             I2cTransaction::write(0x2C, vec![0x2C, 0x03]),
-//             I2cTransaction::write(0x2C, vec![0x2D, 0xE8]),
-//             I2cTransaction::write(0x2C, vec![0x15, 0x40]),
-//             I2cTransaction::write(0x2C, vec![0x11, (0x3f & (!0b11)) | 0b01]),
-//             I2cTransaction::write_read(0x2C, vec![0x14], vec![0x11]),
-//             I2cTransaction::write(0x2C, vec![0x14, 0x13]),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x04, 0x7F]),
-//             I2cTransaction::write(0x2C, vec![0x0A, 0x80]),
-//             I2cTransaction::transaction_start(0x2c),
-//             I2cTransaction::write(0x2C, [0x09].to_vec()),
-//             I2cTransaction::write(0x2C, [0x26].to_vec()),
-//             I2cTransaction::transaction_end(0x2c),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x0C]),
-//             I2cTransaction::write(0x2C, vec![0x0D, 0x87]),
-//             I2cTransaction::write_read(0x2C, vec![0x04], vec![0x04]),
-//             I2cTransaction::write_read(0x2C, vec![0x04], vec![0x02]),
-//             I2cTransaction::write_read(0x2C, vec![0x06], vec![0x00]),
-//             I2cTransaction::write_read(0x2C, vec![0x0A], vec![0x02]),
-//             I2cTransaction::write_read(0x2C, vec![0x09], vec![0xA8, 0xCD]),
-//             I2cTransaction::write_read(0x2C, vec![0x0C], vec![0x00]),
-//             // select
-//             I2cTransaction::write_read(0x2C, vec![0x0E], vec![0x99]),
-//             I2cTransaction::write(0x2C, vec![0x0E, 0x19]),
-//             //    transceive
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x04, 0x7F]),
-//             I2cTransaction::write(0x2C, vec![0x0A, 0x80]),
-//             I2cTransaction::transaction_start(0x2c),
-//             I2cTransaction::write(0x2C, [0x09].to_vec()),
-//             I2cTransaction::write(0x2C, [0x93, 32].to_vec()),
-//             I2cTransaction::transaction_end(0x2c),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x0C]),
-//             I2cTransaction::write(0x2C, vec![0x0D, 0x80]),
-//             I2cTransaction::write_read(0x2C, vec![0x04], vec![0x04]),
-//             I2cTransaction::write_read(0x2C, vec![0x04], vec![0x02]),
-//             I2cTransaction::write_read(0x2C, vec![0x06], vec![0x00]),
-//             I2cTransaction::write_read(0x2C, vec![0x0A], vec![0x05]),
-//             I2cTransaction::write_read(0x2C, vec![0x09], vec![0x01, 0x23, 0x45, 0x67, 0x89]),
-//             I2cTransaction::write_read(0x2C, vec![0x0C], vec![0x00]),
-//             //    calculate crc
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x05, 0x04]),
-//             I2cTransaction::write(0x2C, vec![0x0A, 0x80]),
-//             I2cTransaction::transaction_start(0x2c),
-//             I2cTransaction::write(0x2C, [0x09].to_vec()),
-//             I2cTransaction::write(0x2C, [0x93, 0x70, 0x01, 0x23, 0x45, 0x67, 0x00].to_vec()),
-//             I2cTransaction::transaction_end(0x2c),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x03]),
-//             I2cTransaction::write_read(0x2C, vec![0x05], vec![0x04]),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write_read(0x2C, vec![0x22], vec![0xab]),
-//             I2cTransaction::write_read(0x2C, vec![0x21], vec![0xef]),
-//             //    transceive
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x04, 0x7F]),
-//             I2cTransaction::write(0x2C, vec![0x0A, 0x80]),
-//             I2cTransaction::transaction_start(0x2c),
-//             I2cTransaction::write(0x2C, [0x09].to_vec()),
-//             I2cTransaction::write(
-//                 0x2C,
-//                 [0x93, 0x70, 0x01, 0x23, 0x45, 0x67, 0x00, 0xab, 0xef].to_vec(),
-//             ),
-//             I2cTransaction::transaction_end(0x2c),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x0C]),
-//             I2cTransaction::write(0x2C, vec![0x0D, 0x80]),
-//             I2cTransaction::write_read(0x2C, vec![0x04], vec![0x02]),
-//             I2cTransaction::write_read(0x2C, vec![0x06], vec![0x00]),
-//             I2cTransaction::write_read(0x2C, vec![0x0A], vec![0x03]),
-//             I2cTransaction::write_read(0x2C, vec![0x09], vec![0x20, 0x4d, 0x01]),
-//             I2cTransaction::write_read(0x2C, vec![0x0C], vec![0x00]),
-//             //    calculate crc
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write(0x2C, vec![0x05, 0x04]),
-//             I2cTransaction::write(0x2C, vec![0x0A, 0x80]),
-//             I2cTransaction::transaction_start(0x2c),
-//             I2cTransaction::write(0x2C, [0x09].to_vec()),
-//             I2cTransaction::write(0x2C, [0x20].to_vec()),
-//             I2cTransaction::transaction_end(0x2c),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x03]),
-//             I2cTransaction::write_read(0x2C, vec![0x05], vec![0x04]),
-//             I2cTransaction::write(0x2C, vec![0x01, 0x00]),
-//             I2cTransaction::write_read(0x2C, vec![0x22], vec![0x4d]),
-//             I2cTransaction::write_read(0x2C, vec![0x21], vec![0x01]),
-//         ];
-//         let i2c = I2cMock::new(&expectations);
-//         let mut i2c_clone = i2c.clone();
-//
-//         let mut p16 = P16 {
-//             mfrc522: Mfrc522::new(I2cInterface::new(i2c, 0x2C)).init_inner().unwrap(),
-//         };
-//         let tag_id = p16.read_tag_id().unwrap();
-//         assert_eq!(tag_id.as_bytes(), [1, 35, 69, 103]);
-//
-//         i2c_clone.done();
-//     }
-// }
+//! # Unofficial Rust Driver for PiicoDev RFID Module
+//!
+//! ## External Links
+//!
+//! - [Official Hardware Repository]
+//! - [Official MicroPython Repository]
+//! - [Official Product Site]
+//! - [Datasheet]
+//!
+//! [Official Hardware Repository]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-Module
+//! [Official MicroPython Repository]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-MicroPython-Module
+//! [Official Product Site]: https://piico.dev/p16
+//! [Datasheet]: https://github.com/CoreElectronics/CE-PiicoDev-RFID-Module/raw/main/Documents/MFRC522.pdf
+
+use crate::Driver;
+use classic::{AccessBits, KeyType, ValueBlock};
+use core::fmt;
+use core::marker::PhantomData;
+use embedded_hal::i2c::I2c;
+use mfrc522::comm::blocking::i2c::I2cInterface;
+pub use mfrc522::error::Error;
+use mfrc522::{AtqA, GenericUid, Mfrc522, State, Uid};
+pub use mfrc522::{Initialized, Uninitialized};
+
+const PAGE_SIZE: usize = 4;
+const CAPABILITY_CONTAINER: u8 = 3;
+const FIRST_DATA_PAGE: u8 = 4;
+const TLV_NULL: u8 = 0x00;
+const TLV_NDEF: u8 = 0x03;
+const TLV_TERMINATOR: u8 = 0xFE;
+const TLV_LONG_LENGTH: u8 = 0xFF;
+/// Select acknowledge of a MIFARE Classic 1K, only stored alongside the UID.
+const SAK_CLASSIC: u8 = 0x08;
+const AUTHENTICATE_KEY_B: u8 = 0x61;
+const INCREMENT: u8 = 0xC1;
+const DECREMENT: u8 = 0xC0;
+const TRANSFER: u8 = 0xB0;
+
+#[derive(Debug, PartialEq)]
+pub enum NdefError<E> {
+    Rfid(Error<E>),
+    Ndef(ndef::Error),
+    /// The tag is not formatted for NDEF or holds no NDEF message.
+    NotFound,
+    /// The message does not fit in the data area of the tag.
+    TooLarge,
+}
+
+impl<E> From<Error<E>> for NdefError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Rfid(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClassicError<E> {
+    Rfid(Error<E>),
+    /// The sector trailer's inverted access bits do not match.
+    InvalidAccessBits,
+    /// The block is not formatted as a value block.
+    InvalidValueBlock,
+    /// The amount is larger than a value block can hold.
+    Overflow,
+}
+
+impl<E> From<Error<E>> for ClassicError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Rfid(error)
+    }
+}
+
+/// The UID of a tag, 4, 7 or 10 bytes long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TagId {
+    bytes: [u8; 10],
+    length: usize,
+    compliant: bool,
+}
+
+/// Tag families as far as they can be told apart from the UID and select acknowledge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagType {
+    /// MIFARE Classic 1K or 4K (single size UID).
+    MifareClassic,
+    /// MIFARE Ultralight or NTAG (double size UID).
+    MifareUltralight,
+    /// Tags supporting ISO 14443-4, e.g. `DESFire` or phones emulating a tag.
+    Iso14443_4,
+    Unknown,
+}
+
+impl From<&Uid> for TagId {
+    fn from(uid: &Uid) -> Self {
+        let mut bytes = [0; 10];
+        let uid_bytes = uid.as_bytes();
+        bytes[..uid_bytes.len()].copy_from_slice(uid_bytes);
+        let compliant = match uid {
+            Uid::Single(u) => u.is_compliant(),
+            Uid::Double(u) => u.is_compliant(),
+            Uid::Triple(u) => u.is_compliant(),
+        };
+        Self {
+            bytes,
+            length: uid_bytes.len(),
+            compliant,
+        }
+    }
+}
+
+impl TagId {
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+
+    /// Best guess at the tag family.  The MFRC522 driver does not expose the full select
+    /// acknowledge so this relies on the UID length and ISO 14443-4 compliance.
+    #[must_use]
+    pub const fn tag_type(&self) -> TagType {
+        match (self.compliant, self.length) {
+            (true, _) => TagType::Iso14443_4,
+            (false, 4) => TagType::MifareClassic,
+            (false, 7) => TagType::MifareUltralight,
+            _ => TagType::Unknown,
+        }
+    }
+}
+
+/// Upper case hex without separators, e.g. `04A1B2C3D4E580`.
+impl fmt::Display for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(self, f)
+    }
+}
+
+impl fmt::UpperHex for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_bytes()
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02X}"))
+    }
+}
+
+impl fmt::LowerHex for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_bytes()
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+pub struct P16<I2C: I2c, S: State> {
+    i2c: I2C,
+    address: u8,
+    state: PhantomData<S>,
+}
+
+impl<I2C: I2c> Driver<I2C, Error<I2C::Error>> for P16<I2C, Uninitialized> {
+    fn new_inner(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<I2C: I2c> P16<I2C, Uninitialized> {
+    /// Resets the MFRC522 and turns on the antenna.  Unlike most drivers this changes the type
+    /// of the handle, as tag operations are only available once initialized, so it is used in
+    /// place of [`Driver::init`].
+    #[allow(clippy::same_name_method)]
+    pub fn init(mut self) -> Result<P16<I2C, Initialized>, Error<I2C::Error>> {
+        Mfrc522::new(I2cInterface::new(&mut self.i2c, self.address)).init()?;
+        Ok(P16 {
+            i2c: self.i2c,
+            address: self.address,
+            state: PhantomData,
+        })
+    }
+}
+
+// Method paths in place of the closures passed to `P16::mfrc522` are not general over the
+// lifetime of the borrowed bus.
+#[allow(clippy::redundant_closure_for_method_calls)]
+impl<I2C: I2c> P16<I2C, Initialized> {
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Runs `operation` against the MFRC522 driver, which borrows the bus for its duration.
+    fn mfrc522<T, F>(&mut self, operation: F) -> Result<T, Error<I2C::Error>>
+    where
+        F: FnOnce(&mut bus::Handle<'_, I2C>) -> Result<T, Error<I2C::Error>>,
+    {
+        bus::with_handle(&mut self.i2c, self.address, operation)
+    }
+
+    /// Direct register access for the MIFARE Classic commands the MFRC522 driver lacks.
+    const fn registers(&mut self) -> bus::Registers<'_, I2C> {
+        bus::Registers {
+            i2c: &mut self.i2c,
+            address: self.address,
+        }
+    }
+
+    pub fn version(&mut self) -> Result<u8, Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.version())
+    }
+
+    fn present(result: Result<AtqA, Error<I2C::Error>>) -> Result<Option<AtqA>, Error<I2C::Error>> {
+        match result {
+            Ok(atqa) => Ok(Some(atqa)),
+            Err(Error::Timeout) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Selects a tag that has not been halted, if any is in the field.  A selected tag ignores
+    /// the next poll, so a tag held in the field shows up on every other call.
+    pub fn poll(&mut self) -> Result<Option<TagId>, Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| {
+            Self::present(mfrc522.new_card_present())?
+                .map(|atqa| mfrc522.select(&atqa).map(|uid| TagId::from(&uid)))
+                .transpose()
+        })
+    }
+
+    /// Like [`P16::poll`] but also wakes halted tags.
+    pub fn poll_all(&mut self) -> Result<Option<TagId>, Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| {
+            Self::present(mfrc522.wupa())?
+                .map(|atqa| mfrc522.select(&atqa).map(|uid| TagId::from(&uid)))
+                .transpose()
+        })
+    }
+
+    pub fn read_tag_id(&mut self) -> Result<TagId, Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| {
+            let atqa = mfrc522.reqa()?;
+            let uid = mfrc522.select(&atqa)?;
+            Ok(TagId::from(&uid))
+        })
+    }
+
+    /// Halts the selected tag so that it ignores [`P16::poll`] until it leaves the field or is
+    /// woken by [`P16::poll_all`].
+    pub fn halt(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.hlta())
+    }
+
+    /// Reads four consecutive pages of the selected NTAG or Ultralight tag starting at `page`.
+    pub fn read_pages(&mut self, page: u8) -> Result<[u8; 16], Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.mf_read(page))
+    }
+
+    pub fn read_page(&mut self, page: u8) -> Result<[u8; PAGE_SIZE], Error<I2C::Error>> {
+        let data = self.read_pages(page)?;
+        Ok([data[0], data[1], data[2], data[3]])
+    }
+
+    /// Writes a page of the selected NTAG or Ultralight tag using the compatibility write
+    /// command.  Pages 2 and 3 hold lock bits and the capability container, which can only ever
+    /// have bits set.
+    pub fn write_page(&mut self, page: u8, data: [u8; PAGE_SIZE]) -> Result<(), Error<I2C::Error>> {
+        let mut block = [0; 16];
+        block[..PAGE_SIZE].copy_from_slice(&data);
+        self.mfrc522(|mfrc522| mfrc522.mf_write(page, block))
+    }
+
+    /// Authenticates with key A or key B of the sector containing `block` of the selected
+    /// MIFARE Classic tag.  A wrong key gives [`Error::Timeout`] and leaves the tag idle, so it
+    /// has to be polled again.
+    pub fn authenticate(
+        &mut self,
+        tag_id: &TagId,
+        block: u8,
+        key_type: KeyType,
+        key: &[u8; 6],
+    ) -> Result<(), Error<I2C::Error>> {
+        // Tags with a 7 byte UID expect the last 4 bytes, as given by NXP AN10927.
+        let bytes = tag_id.as_bytes();
+        let uid: [u8; 4] = core::array::from_fn(|i| bytes[bytes.len() - 4 + i]);
+        match key_type {
+            KeyType::A => self.mfrc522(|mfrc522| {
+                mfrc522.mf_authenticate(&Uid::Single(GenericUid::new(uid, SAK_CLASSIC)), block, key)
+            }),
+            KeyType::B => self
+                .registers()
+                .authenticate(AUTHENTICATE_KEY_B, block, *key, uid),
+        }
+    }
+
+    /// Ends an authenticated session.  Must be called before talking to another tag.
+    pub fn deauthenticate(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.stop_crypto1())
+    }
+
+    /// Reads a block of the authenticated sector.  Key A always reads back as zeros.
+    pub fn read_block(&mut self, block: u8) -> Result<[u8; 16], Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.mf_read(block))
+    }
+
+    /// Writes a block of the authenticated sector.  Writing a trailer with malformed access
+    /// bits permanently locks the sector.
+    pub fn write_block(&mut self, block: u8, data: [u8; 16]) -> Result<(), Error<I2C::Error>> {
+        self.mfrc522(|mfrc522| mfrc522.mf_write(block, data))
+    }
+
+    /// Reads the access bits from the trailer of `sector`, which must be authenticated.
+    pub fn read_access_bits(&mut self, sector: u8) -> Result<AccessBits, ClassicError<I2C::Error>> {
+        let trailer = self.read_block(classic::trailer_block(sector))?;
+        AccessBits::from_bytes([trailer[6], trailer[7], trailer[8]])
+            .map_err(|_| ClassicError::InvalidAccessBits)
+    }
+
+    pub fn read_value(&mut self, block: u8) -> Result<ValueBlock, ClassicError<I2C::Error>> {
+        ValueBlock::from_bytes(&self.read_block(block)?)
+            .map_err(|_| ClassicError::InvalidValueBlock)
+    }
+
+    /// Formats a block as a value block.  Use [`P16::increment`] and [`P16::decrement`] to change
+    /// the value afterwards, as they are atomic on the tag and may be allowed by the access
+    /// conditions when writing is not.
+    pub fn write_value(&mut self, block: u8, value: ValueBlock) -> Result<(), Error<I2C::Error>> {
+        self.write_block(block, value.to_bytes())
+    }
+
+    /// Applies `command` with `amount` to the value block `block` and transfers the result back
+    /// to it.
+    fn change_value(
+        &mut self,
+        command: u8,
+        block: u8,
+        amount: u32,
+    ) -> Result<(), ClassicError<I2C::Error>> {
+        let operand = i32::try_from(amount).map_err(|_| ClassicError::Overflow)?;
+        let mut registers = self.registers();
+        registers.send_acknowledged(&[command, block])?;
+        registers.send_unacknowledged(&operand.to_le_bytes())?;
+        registers.send_acknowledged(&[TRANSFER, block])?;
+        Ok(())
+    }
+
+    /// Adds `amount` to the value block `block` of the authenticated sector.  Amounts above
+    /// `i32::MAX` give [`ClassicError::Overflow`] without talking to the tag.
+    pub fn increment(&mut self, block: u8, amount: u32) -> Result<(), ClassicError<I2C::Error>> {
+        self.change_value(INCREMENT, block, amount)
+    }
+
+    /// Subtracts `amount` from the value block `block` of the authenticated sector.  Amounts
+    /// above `i32::MAX` give [`ClassicError::Overflow`] without talking to the tag.
+    pub fn decrement(&mut self, block: u8, amount: u32) -> Result<(), ClassicError<I2C::Error>> {
+        self.change_value(DECREMENT, block, amount)
+    }
+
+    /// Size in bytes of the NDEF data area as given by the capability container.
+    fn data_area(&mut self) -> Result<usize, Error<I2C::Error>> {
+        Ok(usize::from(self.read_page(CAPABILITY_CONTAINER)?[2]) * 8)
+    }
+
+    fn read_data(&mut self, offset: usize, out: &mut [u8]) -> Result<(), NdefError<I2C::Error>> {
+        let mut position = 0;
+        while position < out.len() {
+            let absolute = offset + position;
+            let page = u8::try_from(absolute / PAGE_SIZE)
+                .ok()
+                .and_then(|page| page.checked_add(FIRST_DATA_PAGE))
+                .ok_or(NdefError::TooLarge)?;
+            let data = self.read_pages(page)?;
+            let skip = absolute % PAGE_SIZE;
+            let count = (data.len() - skip).min(out.len() - position);
+            out[position..position + count].copy_from_slice(&data[skip..skip + count]);
+            position += count;
+        }
+        Ok(())
+    }
+
+    /// Reads the first NDEF message from the selected NTAG or Ultralight tag into `buffer`.  The
+    /// message can be decoded with [`ndef::decode`].
+    pub fn read_ndef<'b>(
+        &mut self,
+        buffer: &'b mut [u8],
+    ) -> Result<&'b [u8], NdefError<I2C::Error>> {
+        let capacity = self.data_area()?;
+        let mut offset = 0;
+        while offset < capacity {
+            let mut header = [0; 4];
+            self.read_data(offset, &mut header)?;
+            match header {
+                [TLV_NULL, ..] => offset += 1,
+                [TLV_TERMINATOR, ..] => break,
+                [tag, size, high, low] => {
+                    let (length, start) = if size == TLV_LONG_LENGTH {
+                        (usize::from(u16::from_be_bytes([high, low])), 4)
+                    } else {
+                        (usize::from(size), 2)
+                    };
+                    if offset + start + length > capacity {
+                        return Err(NdefError::Ndef(ndef::Error::Malformed));
+                    }
+                    if tag == TLV_NDEF {
+                        let message = buffer
+                            .get_mut(..length)
+                            .ok_or(NdefError::Ndef(ndef::Error::BufferTooSmall))?;
+                        self.read_data(offset + start, message)?;
+                        return Ok(message);
+                    }
+                    offset += start + length;
+                }
+            }
+        }
+        Err(NdefError::NotFound)
+    }
+
+    /// Replaces the contents of the selected NTAG or Ultralight tag with a single NDEF message,
+    /// as encoded by [`ndef::encode`].
+    pub fn write_ndef(&mut self, message: &[u8]) -> Result<(), NdefError<I2C::Error>> {
+        let capacity = self.data_area()?;
+        if capacity == 0 {
+            return Err(NdefError::NotFound);
+        }
+        let length = u16::try_from(message.len()).map_err(|_| NdefError::TooLarge)?;
+        let (header, header_length) = match u8::try_from(length) {
+            Ok(short) if short != TLV_LONG_LENGTH => ([TLV_NDEF, short, 0, 0], 2),
+            _ => {
+                let [high, low] = length.to_be_bytes();
+                ([TLV_NDEF, TLV_LONG_LENGTH, high, low], 4)
+            }
+        };
+        let total = header_length + message.len() + 1;
+        if total > capacity {
+            return Err(NdefError::TooLarge);
+        }
+        let byte = |index: usize| {
+            if index < header_length {
+                header[index]
+            } else if index < header_length + message.len() {
+                message[index - header_length]
+            } else if index == header_length + message.len() {
+                TLV_TERMINATOR
+            } else {
+                0
+            }
+        };
+        for (page, start) in (FIRST_DATA_PAGE..).zip((0..total).step_by(PAGE_SIZE)) {
+            self.write_page(page, core::array::from_fn(|index| byte(start + index)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::{format, vec::Vec};
+
+    use crate::p16::classic::{AccessBits, KeyType, ValueBlock, DEFAULT_KEY};
+    use crate::p16::ndef::{self, Record, Uri};
+    use crate::p16::simulator::{Card, CardState, Simulator};
+    use crate::p16::{ClassicError, Error, NdefError, TagId, TagType, P16};
+    use crate::Driver;
+
+    #[test]
+    pub fn new() {
+        let mut p16 = P16::new(Simulator::new(None), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+
+        assert_eq!(p16.version(), Ok(0x92));
+        assert_eq!(p16.poll(), Ok(None));
+        assert_eq!(p16.read_tag_id(), Err(Error::Timeout));
+        assert!(P16::new(Simulator::new(None), 0x78).is_err());
+    }
+
+    #[test]
+    pub fn read_tag_id_classic() {
+        let simulator = Simulator::new(Some(Card::classic(&[0x01, 0x23, 0x45, 0x67])));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+
+        let tag_id = p16.read_tag_id().unwrap();
+        assert_eq!(tag_id.as_bytes(), [0x01, 0x23, 0x45, 0x67]);
+        assert_eq!(tag_id.tag_type(), TagType::MifareClassic);
+        assert_eq!(format!("{tag_id}"), "01234567");
+    }
+
+    #[test]
+    pub fn poll_ntag() {
+        let uid = [0x04, 0xA1, 0xB2, 0xC3, 0xD4, 0xE5, 0x80];
+        let simulator = Simulator::new(Some(Card::ntag(uid)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+
+        let tag_id = p16.poll().unwrap().unwrap();
+        assert_eq!(tag_id.as_bytes(), uid);
+        assert_eq!(tag_id.tag_type(), TagType::MifareUltralight);
+        assert_eq!(format!("{tag_id}"), "04A1B2C3D4E580");
+        assert_eq!(format!("{tag_id:x}"), "04a1b2c3d4e580");
+        assert_eq!(p16.poll(), Ok(None));
+        assert_eq!(p16.poll().unwrap(), Some(tag_id));
+    }
+
+    #[test]
+    pub fn poll_triple_size_iso14443_4() {
+        let uid = [0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        let simulator = Simulator::new(Some(Card::new(&uid, [0x84, 0x00], 0x20)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+
+        let tag_id: TagId = p16.poll().unwrap().unwrap();
+        assert_eq!(tag_id.as_bytes(), uid);
+        assert_eq!(tag_id.tag_type(), TagType::Iso14443_4);
+    }
+
+    #[test]
+    pub fn halt() {
+        let simulator = Simulator::new(Some(Card::classic(&[0x01, 0x23, 0x45, 0x67])));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+
+        assert!(p16.poll().unwrap().is_some());
+        assert_eq!(p16.halt(), Ok(()));
+        assert_eq!(p16.poll(), Ok(None));
+        assert_eq!(
+            p16.poll_all().unwrap().unwrap().as_bytes(),
+            [1, 35, 69, 103]
+        );
+
+        let released = p16.release();
+        assert_eq!(released.card.unwrap().state, CardState::Active);
+    }
+
+    const NTAG_UID: [u8; 7] = [0x04, 0xA1, 0xB2, 0xC3, 0xD4, 0xE5, 0x80];
+
+    #[test]
+    pub fn read_and_write_pages() {
+        let simulator = Simulator::new(Some(Card::ntag(NTAG_UID)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+        p16.poll().unwrap().unwrap();
+
+        assert_eq!(p16.read_page(3), Ok([0xE1, 0x10, 0x12, 0x00]));
+        assert_eq!(p16.write_page(8, [1, 2, 3, 4]), Ok(()));
+        assert_eq!(p16.write_page(9, [5, 6, 7, 8]), Ok(()));
+        assert_eq!(
+            p16.read_pages(7),
+            Ok([0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0])
+        );
+        assert_eq!(p16.read_pages(44).unwrap()[4..7], NTAG_UID[..3]);
+        assert_eq!(p16.write_page(0, [0; 4]), Err(Error::Timeout));
+    }
+
+    #[test]
+    pub fn ndef_round_trip() {
+        let simulator = Simulator::new(Some(Card::ntag(NTAG_UID)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+        p16.poll().unwrap().unwrap();
+
+        let mut buffer = [0; 144];
+        assert_eq!(p16.read_ndef(&mut buffer), Ok(&[][..]));
+
+        let records = [
+            Record::Uri(Uri::new("https://piico.dev/p16")),
+            Record::Text {
+                language: "en",
+                text: "Workshop check-in",
+            },
+        ];
+        let mut message = [0; 64];
+        let length = ndef::encode(&records, &mut message).unwrap();
+        assert_eq!(p16.write_ndef(&message[..length]), Ok(()));
+
+        let read = p16.read_ndef(&mut buffer).unwrap();
+        assert_eq!(read, &message[..length]);
+        assert_eq!(ndef::decode(read).collect::<Vec<_>>(), records.map(Ok));
+
+        let released = p16.release();
+        let memory = &released.card.unwrap().memory;
+        assert_eq!(memory[16..18], [0x03, u8::try_from(length).unwrap()]);
+        assert_eq!(memory[18 + length], 0xFE);
+    }
+
+    #[test]
+    pub fn ndef_skips_other_tlvs() {
+        let mut card = Card::ntag(NTAG_UID);
+        card.memory[16..28].copy_from_slice(&[
+            0x01, 0x03, 0xA0, 0x0C, 0x34, 0x00, 0x03, 0x05, 0xD1, 0x01, 0x01, 0x55,
+        ]);
+        card.memory[28..30].copy_from_slice(&[0x00, 0xFE]);
+        let mut p16 = P16::new(Simulator::new(Some(card)), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+        p16.poll().unwrap().unwrap();
+
+        let mut buffer = [0; 4];
+        assert_eq!(
+            p16.read_ndef(&mut buffer),
+            Err(NdefError::Ndef(ndef::Error::BufferTooSmall))
+        );
+        let mut larger_buffer = [0; 16];
+        assert_eq!(
+            p16.read_ndef(&mut larger_buffer),
+            Ok(&[0xD1, 0x01, 0x01, 0x55, 0x00][..])
+        );
+    }
+
+    #[test]
+    pub fn ndef_errors() {
+        let mut card = Card::ntag(NTAG_UID);
+        card.memory[16] = 0xFE;
+        let mut p16 = P16::new(Simulator::new(Some(card)), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+        p16.poll().unwrap().unwrap();
+
+        let mut buffer = [0; 16];
+        assert_eq!(p16.read_ndef(&mut buffer), Err(NdefError::NotFound));
+        assert_eq!(p16.write_ndef(&[0; 142]), Err(NdefError::TooLarge));
+        assert_eq!(p16.write_ndef(&[0; 141]), Ok(()));
+    }
+
+    const CLASSIC_UID: [u8; 4] = [0x01, 0x23, 0x45, 0x67];
+
+    #[test]
+    pub fn classic_blocks() {
+        let simulator = Simulator::new(Some(Card::classic(&CLASSIC_UID)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+        let tag_id = p16.poll().unwrap().unwrap();
+
+        assert_eq!(p16.read_block(4), Err(Error::Crc));
+        assert_eq!(p16.poll(), Ok(Some(tag_id)));
+        assert_eq!(
+            p16.authenticate(&tag_id, 4, KeyType::A, &DEFAULT_KEY),
+            Ok(())
+        );
+        assert_eq!(p16.read_block(5), Ok([0; 16]));
+        assert_eq!(p16.write_block(5, [0xA5; 16]), Ok(()));
+        assert_eq!(p16.read_block(5), Ok([0xA5; 16]));
+        assert_eq!(
+            p16.read_block(7),
+            Ok([0, 0, 0, 0, 0, 0, 0xFF, 0x07, 0x80, 0x69, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])
+        );
+        assert_eq!(p16.read_access_bits(1), Ok(AccessBits::TRANSPORT));
+        assert_eq!(p16.read_block(8), Err(Error::Crc));
+
+        assert_eq!(p16.poll(), Ok(Some(tag_id)));
+        assert_eq!(
+            p16.authenticate(&tag_id, 0, KeyType::A, &DEFAULT_KEY),
+            Ok(())
+        );
+        assert_eq!(
+            p16.read_block(0).unwrap()[..5],
+            [0x01, 0x23, 0x45, 0x67, 0x00]
+        );
+        assert_eq!(p16.deauthenticate(), Ok(()));
+        assert_eq!(p16.read_block(0), Err(Error::Crc));
+
+        let released = p16.release();
+        assert_eq!(released.card.unwrap().memory[80..96], [0xA5; 16]);
+    }
+
+    #[test]
+    pub fn classic_wrong_key() {
+        let mut card = Card::classic(&CLASSIC_UID);
+        card.memory[0x3F * 16..0x3F * 16 + 6].copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+        card.memory[0x3F * 16 + 7] = 0x80;
+        let mut p16 = P16::new(Simulator::new(Some(card)), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+        let tag_id = p16.poll().unwrap().unwrap();
+
+        assert_eq!(
+            p16.authenticate(&tag_id, 0x3C, KeyType::A, &DEFAULT_KEY),
+            Err(Error::Timeout)
+        );
+        assert_eq!(p16.poll(), Ok(Some(tag_id)));
+        assert_eq!(
+            p16.authenticate(&tag_id, 0x3C, KeyType::A, &[1, 2, 3, 4, 5, 6]),
+            Ok(())
+        );
+        assert_eq!(
+            p16.read_access_bits(15),
+            Err(ClassicError::InvalidAccessBits)
+        );
+    }
+
+    #[test]
+    pub fn classic_key_b() {
+        let mut card = Card::classic(&CLASSIC_UID);
+        card.memory[0x07 * 16 + 10..0x08 * 16].copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+        let mut p16 = P16::new(Simulator::new(Some(card)), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+        let tag_id = p16.poll().unwrap().unwrap();
+
+        assert_eq!(
+            p16.authenticate(&tag_id, 4, KeyType::B, &DEFAULT_KEY),
+            Err(Error::Timeout)
+        );
+        assert_eq!(p16.poll(), Ok(Some(tag_id)));
+        assert_eq!(
+            p16.authenticate(&tag_id, 4, KeyType::B, &[1, 2, 3, 4, 5, 6]),
+            Ok(())
+        );
+        assert_eq!(p16.write_block(6, [0x5A; 16]), Ok(()));
+        assert_eq!(p16.read_block(6), Ok([0x5A; 16]));
+    }
+
+    #[test]
+    pub fn classic_seven_byte_uid() {
+        let uid = [0x04, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+        let mut p16 = P16::new(Simulator::new(Some(Card::classic(&uid))), 0x2C)
+            .unwrap()
+            .init()
+            .unwrap();
+        let tag_id = p16.poll().unwrap().unwrap();
+        assert_eq!(tag_id.as_bytes(), uid);
+
+        assert_eq!(
+            p16.authenticate(&tag_id, 0, KeyType::A, &DEFAULT_KEY),
+            Ok(())
+        );
+        assert_eq!(p16.read_block(0).unwrap()[..7], uid);
+        assert_eq!(
+            p16.authenticate(&tag_id, 4, KeyType::B, &DEFAULT_KEY),
+            Ok(())
+        );
+        assert_eq!(p16.read_block(4), Ok([0; 16]));
+    }
+
+    #[test]
+    pub fn classic_values() {
+        let simulator = Simulator::new(Some(Card::classic(&CLASSIC_UID)));
+        let mut p16 = P16::new(simulator, 0x2C).unwrap().init().unwrap();
+        let tag_id = p16.poll().unwrap().unwrap();
+        p16.authenticate(&tag_id, 8, KeyType::A, &DEFAULT_KEY)
+            .unwrap();
+
+        assert_eq!(p16.read_value(9), Err(ClassicError::InvalidValueBlock));
+        let value = ValueBlock {
+            value: 10,
+            address: 9,
+        };
+        assert_eq!(p16.write_value(9, value), Ok(()));
+        assert_eq!(p16.read_value(9), Ok(value));
+
+        assert_eq!(p16.increment(9, 5), Ok(()));
+        assert_eq!(p16.read_value(9).map(|block| block.value), Ok(15_i32));
+        assert_eq!(p16.decrement(9, 20), Ok(()));
+        assert_eq!(
+            p16.read_value(9),
+            Ok(ValueBlock {
+                value: -5,
+                address: 9,
+            })
+        );
+        assert_eq!(p16.increment(9, u32::MAX), Err(ClassicError::Overflow));
+        assert_eq!(p16.increment(10, 1), Err(ClassicError::Rfid(Error::Nak)));
+
+        let released = p16.release();
+        assert_eq!(
+            released.card.unwrap().memory[9 * 16..10 * 16],
+            ValueBlock {
+                value: -5,
+                address: 9,
+            }
+            .to_bytes()
+        );
+    }
+}
+
+mod bus;
+pub mod classic;
+pub mod ndef;
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod simulator;
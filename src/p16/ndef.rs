@@ -0,0 +1,421 @@
+//! A `no_std` encoder and decoder for NDEF messages, as written to tags by phones.
+//!
+//! Text and URI records are understood.  Any other record is passed through untouched as
+//! [`Record::Other`].  Chunked records are not supported.
+
+use core::fmt;
+use core::str::from_utf8;
+
+const MESSAGE_BEGIN: u8 = 0x80;
+const MESSAGE_END: u8 = 0x40;
+const CHUNK: u8 = 0x20;
+const SHORT_RECORD: u8 = 0x10;
+const ID_LENGTH: u8 = 0x08;
+const TNF: u8 = 0x07;
+
+const TNF_WELL_KNOWN: u8 = 0x01;
+const TYPE_TEXT: &[u8] = b"T";
+const TYPE_URI: &[u8] = b"U";
+const UTF16: u8 = 0x80;
+const LANGUAGE_LENGTH: u8 = 0x3F;
+
+/// Abbreviations defined by the NFC Forum URI record type definition, indexed by their code.
+const URI_PREFIXES: [&str; 36] = [
+    "",
+    "http://www.",
+    "https://www.",
+    "http://",
+    "https://",
+    "tel:",
+    "mailto:",
+    "ftp://anonymous:anonymous@",
+    "ftp://ftp.",
+    "ftps://",
+    "sftp://",
+    "smb://",
+    "nfs://",
+    "ftp://",
+    "dav://",
+    "news:",
+    "telnet://",
+    "imap:",
+    "rtsp://",
+    "urn:",
+    "pop:",
+    "sip:",
+    "sips:",
+    "tftp:",
+    "btspp://",
+    "btl2cap://",
+    "btgoep://",
+    "tcpobex://",
+    "irdaobex://",
+    "file://",
+    "urn:epc:id:",
+    "urn:epc:tag:",
+    "urn:epc:pat:",
+    "urn:epc:raw:",
+    "urn:epc:",
+    "urn:nfc:",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    BufferTooSmall,
+    Malformed,
+}
+
+/// A URI split into its abbreviated prefix and the remainder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Uri<'a> {
+    prefix: u8,
+    rest: &'a str,
+}
+
+impl<'a> Uri<'a> {
+    /// Splits off the longest known prefix of `uri`.
+    #[must_use]
+    pub fn new(uri: &'a str) -> Self {
+        let (prefix, abbreviation) = URI_PREFIXES
+            .iter()
+            .enumerate()
+            .filter(|(_, abbreviation)| uri.starts_with(*abbreviation))
+            .max_by_key(|(_, abbreviation)| abbreviation.len())
+            .unwrap_or((0, &""));
+        Self {
+            prefix: u8::try_from(prefix).unwrap_or(0),
+            rest: &uri[abbreviation.len()..],
+        }
+    }
+
+    #[must_use]
+    pub fn prefix(&self) -> &'static str {
+        URI_PREFIXES[usize::from(self.prefix)]
+    }
+
+    #[must_use]
+    pub const fn rest(&self) -> &'a str {
+        self.rest
+    }
+}
+
+impl fmt::Display for Uri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.prefix(), self.rest)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Record<'a> {
+    /// UTF-8 text with an IANA language code such as `en`.
+    Text {
+        language: &'a str,
+        text: &'a str,
+    },
+    Uri(Uri<'a>),
+    /// Any other record, including UTF-16 text.
+    Other {
+        tnf: u8,
+        record_type: &'a [u8],
+        id: &'a [u8],
+        payload: &'a [u8],
+    },
+}
+
+struct Writer<'b> {
+    buffer: &'b mut [u8],
+    position: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self.position + bytes.len();
+        self.buffer
+            .get_mut(self.position..end)
+            .ok_or(Error::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+}
+
+impl Record<'_> {
+    const fn header(&self) -> (u8, &[u8], &[u8]) {
+        match *self {
+            Self::Text { .. } => (TNF_WELL_KNOWN, TYPE_TEXT, &[]),
+            Self::Uri(_) => (TNF_WELL_KNOWN, TYPE_URI, &[]),
+            Self::Other {
+                tnf,
+                record_type,
+                id,
+                ..
+            } => (tnf & TNF, record_type, id),
+        }
+    }
+
+    const fn payload_length(&self) -> usize {
+        match *self {
+            Self::Text { language, text } => 1 + language.len() + text.len(),
+            Self::Uri(uri) => 1 + uri.rest.len(),
+            Self::Other { payload, .. } => payload.len(),
+        }
+    }
+
+    fn write_payload(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        match *self {
+            Self::Text { language, text } => {
+                let status = u8::try_from(language.len())
+                    .ok()
+                    .filter(|length| *length <= LANGUAGE_LENGTH)
+                    .ok_or(Error::Malformed)?;
+                writer.put(&[status])?;
+                writer.put(language.as_bytes())?;
+                writer.put(text.as_bytes())
+            }
+            Self::Uri(uri) => {
+                writer.put(&[uri.prefix])?;
+                writer.put(uri.rest.as_bytes())
+            }
+            Self::Other { payload, .. } => writer.put(payload),
+        }
+    }
+}
+
+/// Encodes `records` as a single NDEF message into `buffer`, returning the length used.
+pub fn encode(records: &[Record<'_>], buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut writer = Writer {
+        buffer,
+        position: 0,
+    };
+    for (index, record) in records.iter().enumerate() {
+        let (tnf, record_type, id) = record.header();
+        let payload_length = record.payload_length();
+        let short = u8::try_from(payload_length).is_ok();
+        let mut flags = tnf;
+        if index == 0 {
+            flags |= MESSAGE_BEGIN;
+        }
+        if index + 1 == records.len() {
+            flags |= MESSAGE_END;
+        }
+        if short {
+            flags |= SHORT_RECORD;
+        }
+        if !id.is_empty() {
+            flags |= ID_LENGTH;
+        }
+        let type_length = u8::try_from(record_type.len()).map_err(|_| Error::Malformed)?;
+        writer.put(&[flags, type_length])?;
+        if short {
+            writer.put(&[u8::try_from(payload_length).map_err(|_| Error::Malformed)?])?;
+        } else {
+            let length = u32::try_from(payload_length).map_err(|_| Error::Malformed)?;
+            writer.put(&length.to_be_bytes())?;
+        }
+        if !id.is_empty() {
+            writer.put(&[u8::try_from(id.len()).map_err(|_| Error::Malformed)?])?;
+        }
+        writer.put(record_type)?;
+        writer.put(id)?;
+        record.write_payload(&mut writer)?;
+    }
+    Ok(writer.position)
+}
+
+/// Iterates over the records of an NDEF message.  Decoding stops after the first error.
+#[must_use]
+pub const fn decode(message: &[u8]) -> Records<'_> {
+    Records {
+        message,
+        position: 0,
+        done: false,
+    }
+}
+
+pub struct Records<'a> {
+    message: &'a [u8],
+    position: usize,
+    done: bool,
+}
+
+impl<'a> Records<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let end = self.position.checked_add(length).ok_or(Error::Malformed)?;
+        let bytes = self
+            .message
+            .get(self.position..end)
+            .ok_or(Error::Malformed)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn record(&mut self) -> Result<Record<'a>, Error> {
+        let [flags, type_length] = *self.bytes(2)? else {
+            return Err(Error::Malformed);
+        };
+        if flags & CHUNK != 0 {
+            return Err(Error::Malformed);
+        }
+        let payload_length = if flags & SHORT_RECORD == 0 {
+            let bytes: [u8; 4] = self.bytes(4)?.try_into().map_err(|_| Error::Malformed)?;
+            usize::try_from(u32::from_be_bytes(bytes)).map_err(|_| Error::Malformed)?
+        } else {
+            usize::from(self.bytes(1)?[0])
+        };
+        let id_length = if flags & ID_LENGTH == 0 {
+            0
+        } else {
+            self.bytes(1)?[0]
+        };
+        let record_type = self.bytes(usize::from(type_length))?;
+        let id = self.bytes(usize::from(id_length))?;
+        let payload = self.bytes(payload_length)?;
+        if flags & MESSAGE_END != 0 {
+            self.done = true;
+        }
+        let tnf = flags & TNF;
+        match (tnf, record_type, payload) {
+            (TNF_WELL_KNOWN, TYPE_TEXT, [status, rest @ ..]) if status & UTF16 == 0 => {
+                let (language, text) = rest
+                    .split_at_checked(usize::from(status & LANGUAGE_LENGTH))
+                    .ok_or(Error::Malformed)?;
+                Ok(Record::Text {
+                    language: from_utf8(language).map_err(|_| Error::Malformed)?,
+                    text: from_utf8(text).map_err(|_| Error::Malformed)?,
+                })
+            }
+            (TNF_WELL_KNOWN, TYPE_URI, [prefix, rest @ ..]) => {
+                if usize::from(*prefix) >= URI_PREFIXES.len() {
+                    return Err(Error::Malformed);
+                }
+                Ok(Record::Uri(Uri {
+                    prefix: *prefix,
+                    rest: from_utf8(rest).map_err(|_| Error::Malformed)?,
+                }))
+            }
+            _ => Ok(Record::Other {
+                tnf,
+                record_type,
+                id,
+                payload,
+            }),
+        }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<Record<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.position >= self.message.len() {
+            return None;
+        }
+        let record = self.record();
+        if record.is_err() {
+            self.done = true;
+        }
+        Some(record)
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::{format, vec, vec::Vec};
+
+    use crate::p16::ndef::{decode, encode, Error, Record, Uri};
+
+    #[test]
+    pub fn encode_text() {
+        let mut buffer = [0; 32];
+        let length = encode(
+            &[Record::Text {
+                language: "en",
+                text: "Hello",
+            }],
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(
+            buffer[..length],
+            [0xD1, 0x01, 0x08, b'T', 0x02, b'e', b'n', b'H', b'e', b'l', b'l', b'o']
+        );
+    }
+
+    #[test]
+    pub fn encode_uri() {
+        let uri = Uri::new("https://piico.dev/p16");
+        assert_eq!(uri.prefix(), "https://");
+        assert_eq!(uri.rest(), "piico.dev/p16");
+        assert_eq!(format!("{uri}"), "https://piico.dev/p16");
+        assert_eq!(Uri::new("https://www.example.com").prefix(), "https://www.");
+        assert_eq!(Uri::new("geo:0,0").prefix(), "");
+
+        let mut buffer = [0; 32];
+        let length = encode(&[Record::Uri(uri)], &mut buffer).unwrap();
+        let mut expected = vec![0xD1, 0x01, 0x0E, b'U', 0x04];
+        expected.extend_from_slice(b"piico.dev/p16");
+        assert_eq!(buffer[..length], expected);
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let payload = [0x5A; 300];
+        let records = [
+            Record::Uri(Uri::new("mailto:hello@example.com")),
+            Record::Text {
+                language: "en-AU",
+                text: "G'day",
+            },
+            Record::Other {
+                tnf: 0x02,
+                record_type: b"application/octet-stream",
+                id: b"1",
+                payload: &payload,
+            },
+        ];
+        let mut buffer = [0; 400];
+        let length = encode(&records, &mut buffer).unwrap();
+        assert_eq!(buffer[0] & 0xC0, 0x80);
+        assert_eq!(buffer[length - 300 - 32] & 0xD0, 0x40);
+
+        let decoded: Vec<_> = decode(&buffer[..length]).collect();
+        assert_eq!(decoded, records.map(Ok));
+    }
+
+    #[test]
+    pub fn decode_utf16_text_as_other() {
+        let message = [0xD1, 0x01, 0x05, b'T', 0x82, b'e', b'n', 0x00, b'A'];
+        assert_eq!(
+            decode(&message).collect::<Vec<_>>(),
+            [Ok(Record::Other {
+                tnf: 0x01,
+                record_type: b"T",
+                id: &[],
+                payload: &[0x82, b'e', b'n', 0x00, b'A'],
+            })]
+        );
+    }
+
+    #[test]
+    pub fn errors() {
+        let mut buffer = [0; 8];
+        assert_eq!(
+            encode(&[Record::Uri(Uri::new("https://piico.dev"))], &mut buffer),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(
+            decode(&[0xD1, 0x01, 0x08, b'T', 0x02]).collect::<Vec<_>>(),
+            [Err(Error::Malformed)]
+        );
+        assert_eq!(
+            decode(&[0xD1, 0x01, 0x02, b'U', 0x40, b'x']).collect::<Vec<_>>(),
+            [Err(Error::Malformed)]
+        );
+        assert_eq!(decode(&[]).count(), 0);
+
+        let mut records = decode(&[0xD1, 0x01, 0x00, b'T']);
+        records.position = 1;
+        assert_eq!(records.bytes(usize::MAX), Err(Error::Malformed));
+    }
+}
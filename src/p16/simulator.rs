@@ -0,0 +1,470 @@
+//! A simulated MFRC522 with an optional ISO 14443A card in its field.  Tests drive [`P16`] against
+//! this instead of hand written I2C transcripts.
+//!
+//! [`P16`]: super::P16
+
+extern crate std;
+use super::bus::crc_a;
+use super::classic::ValueBlock;
+use core::convert::Infallible;
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+use std::{vec, vec::Vec};
+
+const COMMAND: u8 = 0x01;
+const COM_IRQ: u8 = 0x04;
+const DIV_IRQ: u8 = 0x05;
+const FIFO_DATA: u8 = 0x09;
+const FIFO_LEVEL: u8 = 0x0A;
+const STATUS_2: u8 = 0x08;
+const CONTROL: u8 = 0x0C;
+const BIT_FRAMING: u8 = 0x0D;
+const CRC_HIGH: u8 = 0x21;
+const CRC_LOW: u8 = 0x22;
+const VERSION: u8 = 0x37;
+
+const CALC_CRC: u8 = 0x03;
+const TRANSCEIVE: u8 = 0x0C;
+const MF_AUTHENT: u8 = 0x0E;
+const SOFT_RESET: u8 = 0x0F;
+
+const TIMER_IRQ: u8 = 0x01;
+const CRC_IRQ: u8 = 0x04;
+const IDLE_IRQ: u8 = 0x10;
+const RX_IRQ: u8 = 0x20;
+const CRYPTO_1_ON: u8 = 0x08;
+
+const CASCADE_TAG: u8 = 0x88;
+const READ: u8 = 0x30;
+const WRITE: u8 = 0xA0;
+const AUTHENTICATE_KEY_A: u8 = 0x60;
+const AUTHENTICATE_KEY_B: u8 = 0x61;
+const INCREMENT: u8 = 0xC1;
+const DECREMENT: u8 = 0xC0;
+const TRANSFER: u8 = 0xB0;
+const ACK: u8 = 0x0A;
+const NAK: u8 = 0x00;
+const NTAG213_PAGES: usize = 45;
+const CLASSIC_1K_BLOCKS: usize = 64;
+const BLOCK_SIZE: usize = 16;
+
+fn with_crc(data: &[u8]) -> Vec<u8> {
+    let mut frame = data.to_vec();
+    frame.extend_from_slice(&crc_a(data));
+    frame
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardState {
+    Idle,
+    Ready,
+    Active,
+    Halt,
+}
+
+/// A response frame and the number of valid bits in its last byte (0 meaning all 8).
+type Response = Option<(Vec<u8>, u8)>;
+
+pub struct Card {
+    pub uid: Vec<u8>,
+    pub atqa: [u8; 2],
+    pub sak: u8,
+    pub state: CardState,
+    pub memory: Vec<u8>,
+    /// Memory is addressed in 16 byte blocks behind per sector authentication rather than 4
+    /// byte pages.
+    pub classic: bool,
+    authenticated: Option<usize>,
+    pending_write: Option<usize>,
+    /// An increment or decrement waiting for its operand, and the byte offset of its block.
+    pending_value: Option<(u8, usize)>,
+    /// The result of the last increment or decrement, waiting to be transferred.
+    transfer: Option<i32>,
+}
+
+impl Card {
+    pub fn new(uid: &[u8], atqa: [u8; 2], sak: u8) -> Self {
+        Self {
+            uid: uid.to_vec(),
+            atqa,
+            sak,
+            state: CardState::Idle,
+            memory: Vec::new(),
+            classic: false,
+            authenticated: None,
+            pending_write: None,
+            pending_value: None,
+            transfer: None,
+        }
+    }
+
+    /// A MIFARE Classic 1K with a 4 or 7 byte UID and factory default keys and access bits.
+    pub fn classic(uid: &[u8]) -> Self {
+        let atqa = if uid.len() == 7 { 0x44 } else { 0x04 };
+        let mut card = Self::new(uid, [atqa, 0x00], 0x08);
+        card.classic = true;
+        card.memory = vec![0; CLASSIC_1K_BLOCKS * BLOCK_SIZE];
+        card.memory[..uid.len()].copy_from_slice(uid);
+        if uid.len() == 4 {
+            card.memory[4] = uid.iter().fold(0, |bcc, byte| bcc ^ byte);
+            card.memory[5..8].copy_from_slice(&[0x08, 0x04, 0x00]);
+        }
+        for trailer in card.memory.chunks_mut(BLOCK_SIZE * 4) {
+            trailer[BLOCK_SIZE * 3..].copy_from_slice(&[
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x07, 0x80, 0x69, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0xFF,
+            ]);
+        }
+        card
+    }
+
+    /// An NTAG213 with a 7 byte UID, formatted with an empty NDEF message.
+    pub fn ntag(uid: [u8; 7]) -> Self {
+        let mut card = Self::new(&uid, [0x44, 0x00], 0x00);
+        card.memory = vec![0; NTAG213_PAGES * 4];
+        card.memory[..3].copy_from_slice(&uid[..3]);
+        card.memory[3] = CASCADE_TAG ^ uid[0] ^ uid[1] ^ uid[2];
+        card.memory[4..8].copy_from_slice(&uid[3..]);
+        card.memory[8] = uid[3] ^ uid[4] ^ uid[5] ^ uid[6];
+        card.memory[12..20].copy_from_slice(&[0xE1, 0x10, 0x12, 0x00, 0x03, 0x00, 0xFE, 0x00]);
+        card
+    }
+
+    const fn levels(&self) -> usize {
+        match self.uid.len() {
+            4 => 1,
+            7 => 2,
+            _ => 3,
+        }
+    }
+
+    /// The four UID bytes sent at cascade `level`.
+    fn cascade(&self, level: usize) -> Vec<u8> {
+        let last = level + 1 == self.levels();
+        let start = level * 3;
+        if last {
+            self.uid[start..start + 4].to_vec()
+        } else {
+            let mut bytes = vec![CASCADE_TAG];
+            bytes.extend_from_slice(&self.uid[start..start + 3]);
+            bytes
+        }
+    }
+
+    fn respond(&mut self, frame: &[u8], last_bits: u8) -> Response {
+        match (frame, last_bits) {
+            ([0x26], 7) if self.state == CardState::Idle => {
+                self.state = CardState::Ready;
+                Some((self.atqa.to_vec(), 0))
+            }
+            ([0x52], 7) if matches!(self.state, CardState::Idle | CardState::Halt) => {
+                self.state = CardState::Ready;
+                Some((self.atqa.to_vec(), 0))
+            }
+            ([command @ (0x93 | 0x95 | 0x97), rest @ ..], 0) if self.state == CardState::Ready => {
+                self.select(usize::from((command - 0x93) / 2), rest)
+            }
+            ([0x50, 0x00, ..], 0) if frame[2..] == crc_a(&frame[..2]) => {
+                self.state = CardState::Halt;
+                None
+            }
+            _ => self.respond_active(frame),
+        }
+    }
+
+    fn select(&mut self, level: usize, rest: &[u8]) -> Response {
+        if level >= self.levels() {
+            return None;
+        }
+        let cascade = self.cascade(level);
+        let bcc = cascade.iter().fold(0, |bcc, byte| bcc ^ byte);
+        match rest {
+            [0x20] => {
+                let mut response = cascade;
+                response.push(bcc);
+                Some((response, 0))
+            }
+            [0x70, ..] if rest.len() == 8 && rest[1..5] == cascade[..] && rest[5] == bcc => {
+                let mut sent = vec![0x93 + u8::try_from(level * 2).unwrap()];
+                sent.extend_from_slice(&rest[..6]);
+                if rest[6..] != crc_a(&sent) {
+                    return None;
+                }
+                if level + 1 == self.levels() {
+                    self.state = CardState::Active;
+                    Some((with_crc(&[self.sak]), 0))
+                } else {
+                    Some((with_crc(&[0x04]), 0))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Three pass authentication as performed by the MFRC522 MFAuthent command, which sends the
+    /// last 4 bytes of the UID.  Crypto1 itself is not simulated, so later frames are exchanged
+    /// in the clear.
+    fn authenticate(&mut self, frame: &[u8]) -> bool {
+        let sector = match frame {
+            [command @ (AUTHENTICATE_KEY_A | AUTHENTICATE_KEY_B), block, key @ .., _, _, _, _]
+                if self.classic
+                    && self.state == CardState::Active
+                    && key.len() == 6
+                    && frame[8..] == self.uid[self.uid.len() - 4..]
+                    && usize::from(*block) < CLASSIC_1K_BLOCKS =>
+            {
+                let sector = usize::from(*block) / 4;
+                let key_offset = if *command == AUTHENTICATE_KEY_A {
+                    0
+                } else {
+                    10
+                };
+                let trailer = (sector * 4 + 3) * BLOCK_SIZE + key_offset;
+                (self.memory[trailer..trailer + 6] == *key).then_some(sector)
+            }
+            _ => None,
+        };
+        self.authenticated = sector;
+        if sector.is_none() {
+            self.state = CardState::Idle;
+        }
+        sector.is_some()
+    }
+
+    /// Byte range of `address` if it can be accessed.  Classic blocks need their sector to be
+    /// authenticated.
+    fn locate(&self, address: u8) -> Option<usize> {
+        let index = usize::from(address);
+        if !self.classic {
+            return (index * 4 < self.memory.len()).then_some(index * 4);
+        }
+        (index < CLASSIC_1K_BLOCKS && self.authenticated == Some(index / 4))
+            .then_some(index * BLOCK_SIZE)
+    }
+
+    /// Applies the operand of an increment or decrement to the value block at `start`, giving
+    /// `None` if the block does not hold a value or the result overflows.
+    fn apply(&self, command: u8, start: usize, data: &[u8]) -> Option<i32> {
+        let block: &[u8; BLOCK_SIZE] = self.memory[start..start + BLOCK_SIZE].try_into().ok()?;
+        let value = ValueBlock::from_bytes(block).ok()?.value;
+        let operand = i32::from_le_bytes(data.try_into().ok()?);
+        if command == INCREMENT {
+            value.checked_add(operand)
+        } else {
+            value.checked_sub(operand)
+        }
+    }
+
+    fn respond_active(&mut self, frame: &[u8]) -> Response {
+        let pending_write = self.pending_write.take();
+        let pending_value = self.pending_value.take();
+        let size = if self.classic { BLOCK_SIZE } else { 4 };
+        let response = match frame {
+            _ if self.state != CardState::Active || frame.len() < 3 => None,
+            [data @ .., _, _] if frame[data.len()..] != crc_a(data) => None,
+            [data @ .., _, _] if pending_write.is_some() && data.len() == 16 => {
+                let start = pending_write.unwrap_or(0);
+                self.memory[start..start + size].copy_from_slice(&data[..size]);
+                Some((vec![ACK], 4))
+            }
+            [data @ .., _, _] if pending_value.is_some() && data.len() == 4 => {
+                let (command, start) = pending_value.unwrap_or_default();
+                if let Some(value) = self.apply(command, start, data) {
+                    // The tag only answers an operand to refuse it.
+                    self.transfer = Some(value);
+                    return None;
+                }
+                Some((vec![NAK], 4))
+            }
+            [command @ (INCREMENT | DECREMENT), address, _, _]
+                if self.classic && self.locate(*address).is_some() =>
+            {
+                self.pending_value = self.locate(*address).map(|start| (*command, start));
+                Some((vec![ACK], 4))
+            }
+            [TRANSFER, address, _, _]
+                if self.transfer.is_some() && self.locate(*address).is_some() =>
+            {
+                let start = self.locate(*address).unwrap_or(0);
+                let block = ValueBlock {
+                    value: self.transfer.take().unwrap_or_default(),
+                    address: self.memory[start + 12],
+                };
+                self.memory[start..start + BLOCK_SIZE].copy_from_slice(&block.to_bytes());
+                Some((vec![ACK], 4))
+            }
+            [READ, address, _, _] if self.locate(*address).is_some() => {
+                let start = self.locate(*address).unwrap_or(0);
+                let mut data: Vec<u8> = self
+                    .memory
+                    .iter()
+                    .cycle()
+                    .skip(start)
+                    .take(16)
+                    .copied()
+                    .collect();
+                if self.classic && address % 4 == 3 {
+                    data[..6].fill(0);
+                }
+                Some((with_crc(&data), 0))
+            }
+            [WRITE, address @ 1..=u8::MAX, _, _]
+                if (self.classic || *address >= 2) && self.locate(*address).is_some() =>
+            {
+                self.pending_write = self.locate(*address);
+                Some((vec![ACK], 4))
+            }
+            _ => Some((vec![NAK], 4)),
+        };
+        let failed = response
+            .as_ref()
+            .is_none_or(|(data, bits)| *bits == 4 && data[..] == [NAK]);
+        if failed && self.state != CardState::Halt {
+            self.state = CardState::Idle;
+            self.authenticated = None;
+            self.transfer = None;
+        }
+        response
+    }
+}
+
+pub struct Simulator {
+    registers: [u8; 0x40],
+    fifo: Vec<u8>,
+    pub card: Option<Card>,
+}
+
+impl Simulator {
+    pub const fn new(card: Option<Card>) -> Self {
+        Self {
+            registers: [0; 0x40],
+            fifo: Vec::new(),
+            card,
+        }
+    }
+
+    fn read_register(&mut self, register: u8) -> u8 {
+        match register {
+            FIFO_DATA => {
+                if self.fifo.is_empty() {
+                    0
+                } else {
+                    self.fifo.remove(0)
+                }
+            }
+            FIFO_LEVEL => u8::try_from(self.fifo.len()).unwrap(),
+            VERSION => 0x92,
+            _ => self.registers[usize::from(register)],
+        }
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) {
+        match register {
+            COMMAND => {
+                self.registers[usize::from(COMMAND)] = value & 0x0F;
+                match value & 0x0F {
+                    SOFT_RESET => {
+                        self.registers = [0; 0x40];
+                        self.fifo.clear();
+                    }
+                    CALC_CRC => {
+                        let [low, high] = crc_a(&self.fifo);
+                        self.fifo.clear();
+                        self.registers[usize::from(CRC_LOW)] = low;
+                        self.registers[usize::from(CRC_HIGH)] = high;
+                        self.registers[usize::from(DIV_IRQ)] |= CRC_IRQ;
+                    }
+                    MF_AUTHENT => {
+                        let frame = core::mem::take(&mut self.fifo);
+                        let authenticated = self
+                            .card
+                            .as_mut()
+                            .is_some_and(|card| card.authenticate(&frame));
+                        if authenticated {
+                            self.registers[usize::from(STATUS_2)] |= CRYPTO_1_ON;
+                            self.registers[usize::from(COM_IRQ)] |= IDLE_IRQ;
+                        } else {
+                            self.registers[usize::from(COM_IRQ)] |= TIMER_IRQ;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            STATUS_2 => {
+                self.registers[usize::from(STATUS_2)] = value;
+                if value & CRYPTO_1_ON == 0 {
+                    if let Some(card) = self.card.as_mut() {
+                        card.authenticated = None;
+                    }
+                }
+            }
+            COM_IRQ | DIV_IRQ => {
+                let bits = &mut self.registers[usize::from(register)];
+                if value & 0x80 == 0 {
+                    *bits &= !value;
+                } else {
+                    *bits |= value & 0x7F;
+                }
+            }
+            FIFO_DATA => self.fifo.push(value),
+            FIFO_LEVEL => {
+                if value & 0x80 != 0 {
+                    self.fifo.clear();
+                }
+            }
+            BIT_FRAMING => {
+                self.registers[usize::from(BIT_FRAMING)] = value & 0x7F;
+                if value & 0x80 != 0 && self.registers[usize::from(COMMAND)] == TRANSCEIVE {
+                    self.transceive(value & 0x07);
+                }
+            }
+            _ => self.registers[usize::from(register)] = value,
+        }
+    }
+
+    fn transceive(&mut self, last_bits: u8) {
+        let frame = core::mem::take(&mut self.fifo);
+        let response = self
+            .card
+            .as_mut()
+            .and_then(|card| card.respond(&frame, last_bits));
+        if let Some((data, bits)) = response {
+            self.fifo = data;
+            self.registers[usize::from(CONTROL)] = bits;
+            self.registers[usize::from(COM_IRQ)] |= RX_IRQ | IDLE_IRQ;
+        } else {
+            self.registers[usize::from(COM_IRQ)] |= TIMER_IRQ;
+        }
+    }
+}
+
+impl ErrorType for Simulator {
+    type Error = Infallible;
+}
+
+impl I2c for Simulator {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut register = None;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    for &byte in *bytes {
+                        match register {
+                            None => register = Some(byte),
+                            Some(r) => self.write_register(r, byte),
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.read_register(register.unwrap());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,440 @@
+//! Melodies for [`P18`]: an RTTTL parser, built in alert patterns and a player that feeds notes
+//! to the buzzer one at a time.
+//!
+//! RTTTL (Ring Tone Text Transfer Language) looks like `name:d=4,o=5,b=120:8c,8e,g,2c6`.  The
+//! second section sets the default duration, octave and tempo in beats per minute.  Each note
+//! is an optional duration (1 for a whole note up to 32), a letter from `a` to `g` (`h` is `b`
+//! and `p` is a rest), an optional `#`, an optional octave and an optional `.` for dotted.
+
+use super::{Error, P18};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+use fugit::{Hertz, MillisDuration};
+
+const DEFAULT_DURATION: u32 = 4;
+const DEFAULT_OCTAVE: u32 = 6;
+const DEFAULT_TEMPO: u32 = 63;
+const MAX_OCTAVE: u32 = 8;
+/// The slowest tempo at which a whole note still fits in the buzzer's 16 bit duration.
+const MIN_TEMPO: u32 = 4;
+const MAX_TEMPO: u32 = 900;
+/// C8 to B8 in hertz.  Lower octaves halve these.
+const OCTAVE_8: [u32; 12] = [
+    4186, 4435, 4699, 4978, 5274, 5588, 5920, 6272, 6645, 7040, 7459, 7902,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The name, defaults and notes sections are not separated by `:`.
+    MissingSection,
+    InvalidDefault,
+    InvalidNote,
+}
+
+/// A tone, or a rest when `frequency` is `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub frequency: Option<Hertz<u32>>,
+    pub duration: MillisDuration<u32>,
+}
+
+impl Note {
+    #[must_use]
+    pub const fn tone(frequency: u32, duration: u32) -> Self {
+        Self {
+            frequency: Some(Hertz::<u32>::from_raw(frequency)),
+            duration: MillisDuration::<u32>::from_ticks(duration),
+        }
+    }
+
+    #[must_use]
+    pub const fn rest(duration: u32) -> Self {
+        Self {
+            frequency: None,
+            duration: MillisDuration::<u32>::from_ticks(duration),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Defaults {
+    duration: u32,
+    octave: u32,
+    tempo: u32,
+}
+
+const fn valid_duration(duration: u32) -> bool {
+    matches!(duration, 1 | 2 | 4 | 8 | 16 | 32)
+}
+
+fn parse_defaults(section: &str) -> Result<Defaults, ParseError> {
+    let mut defaults = Defaults {
+        duration: DEFAULT_DURATION,
+        octave: DEFAULT_OCTAVE,
+        tempo: DEFAULT_TEMPO,
+    };
+    for setting in section.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (key, text) = setting.split_once('=').ok_or(ParseError::InvalidDefault)?;
+        let value: u32 = text
+            .trim()
+            .parse()
+            .map_err(|_| ParseError::InvalidDefault)?;
+        match key.trim() {
+            "d" if valid_duration(value) => defaults.duration = value,
+            "o" if value <= MAX_OCTAVE => defaults.octave = value,
+            "b" if (MIN_TEMPO..=MAX_TEMPO).contains(&value) => defaults.tempo = value,
+            _ => return Err(ParseError::InvalidDefault),
+        }
+    }
+    Ok(defaults)
+}
+
+/// Splits leading ASCII digits off `text`, failing if they overflow.
+fn digits(text: &str) -> Result<(Option<u32>, &str), ParseError> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, rest) = text.split_at(end);
+    if number.is_empty() {
+        return Ok((None, rest));
+    }
+    let value = number.parse().map_err(|_| ParseError::InvalidNote)?;
+    Ok((Some(value), rest))
+}
+
+fn frequency(octave: u32, semitone: u32) -> Result<u32, ParseError> {
+    let note = octave * 12 + semitone;
+    let shift = MAX_OCTAVE
+        .checked_sub(note / 12)
+        .ok_or(ParseError::InvalidNote)?;
+    let base = OCTAVE_8[usize::try_from(note % 12).map_err(|_| ParseError::InvalidNote)?];
+    Ok((base + (1 << shift >> 1)) >> shift)
+}
+
+fn parse_note(token: &str, defaults: Defaults) -> Result<Note, ParseError> {
+    let (given_duration, after_duration) = digits(token)?;
+    let duration = given_duration.unwrap_or(defaults.duration);
+    if !valid_duration(duration) {
+        return Err(ParseError::InvalidNote);
+    }
+    let mut chars = after_duration.chars();
+    let semitone = match chars.next().map(|c| c.to_ascii_lowercase()) {
+        Some('c') => Some(0),
+        Some('d') => Some(2),
+        Some('e') => Some(4),
+        Some('f') => Some(5),
+        Some('g') => Some(7),
+        Some('a') => Some(9),
+        Some('b' | 'h') => Some(11),
+        Some('p') => None,
+        _ => return Err(ParseError::InvalidNote),
+    };
+    let mut rest = chars.as_str();
+    let sharp = rest.starts_with('#');
+    if sharp {
+        rest = &rest[1..];
+    }
+    let mut dotted = rest.starts_with('.');
+    if dotted {
+        rest = &rest[1..];
+    }
+    let (given_octave, after_octave) = digits(rest)?;
+    let octave = given_octave.unwrap_or(defaults.octave);
+    if octave > MAX_OCTAVE {
+        return Err(ParseError::InvalidNote);
+    }
+    if after_octave == "." {
+        dotted = true;
+    } else if !after_octave.is_empty() {
+        return Err(ParseError::InvalidNote);
+    }
+
+    let whole = 240_000 / defaults.tempo;
+    let milliseconds = if dotted {
+        whole * 3 / (duration * 2)
+    } else {
+        whole / duration
+    };
+    // Dotted notes can still be too long at slow tempos.
+    if milliseconds > u32::from(u16::MAX) {
+        return Err(ParseError::InvalidNote);
+    }
+    match semitone {
+        Some(natural) => Ok(Note::tone(
+            frequency(octave, natural + u32::from(sharp))?,
+            milliseconds,
+        )),
+        None => Ok(Note::rest(milliseconds)),
+    }
+}
+
+/// A parsed RTTTL tune.  Every note is checked by [`Rtttl::parse`], so [`Rtttl::notes`] can not
+/// fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rtttl<'a> {
+    name: &'a str,
+    defaults: Defaults,
+    notes: &'a str,
+}
+
+impl<'a> Rtttl<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, ParseError> {
+        let mut sections = text.splitn(3, ':');
+        let (Some(name), Some(defaults), Some(notes)) =
+            (sections.next(), sections.next(), sections.next())
+        else {
+            return Err(ParseError::MissingSection);
+        };
+        let rtttl = Self {
+            name: name.trim(),
+            defaults: parse_defaults(defaults)?,
+            notes,
+        };
+        rtttl
+            .tokens()
+            .try_for_each(|token| parse_note(token, rtttl.defaults).map(|_| ()))?;
+        Ok(rtttl)
+    }
+
+    #[must_use]
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    fn tokens(&self) -> impl Iterator<Item = &'a str> + Clone {
+        self.notes
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+    }
+
+    pub fn notes(&self) -> impl Iterator<Item = Note> + Clone + 'a {
+        let defaults = self.defaults;
+        self.tokens()
+            .filter_map(move |token| parse_note(token, defaults).ok())
+    }
+}
+
+/// Short patterns for signalling events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alert {
+    Beep,
+    DoubleBeep,
+    /// Rising two tone chime.
+    Success,
+    /// Falling two tone buzz.
+    Failure,
+    /// Fast alternating siren, worth repeating until acknowledged.
+    Alarm,
+}
+
+const BEEP: [Note; 1] = [Note::tone(2000, 100)];
+const DOUBLE_BEEP: [Note; 3] = [Note::tone(2000, 80), Note::rest(80), Note::tone(2000, 80)];
+const SUCCESS: [Note; 2] = [Note::tone(1047, 100), Note::tone(1568, 200)];
+const FAILURE: [Note; 2] = [Note::tone(392, 150), Note::tone(262, 300)];
+const ALARM: [Note; 4] = [
+    Note::tone(2637, 150),
+    Note::tone(1760, 150),
+    Note::tone(2637, 150),
+    Note::tone(1760, 150),
+];
+
+impl Alert {
+    #[must_use]
+    pub const fn notes(self) -> &'static [Note] {
+        match self {
+            Self::Beep => &BEEP,
+            Self::DoubleBeep => &DOUBLE_BEEP,
+            Self::Success => &SUCCESS,
+            Self::Failure => &FAILURE,
+            Self::Alarm => &ALARM,
+        }
+    }
+}
+
+/// Plays a sequence of notes on a [`P18`], either all at once with [`MelodyPlayer::play`] or a
+/// note at a time from a main loop with [`MelodyPlayer::tick`].
+pub struct MelodyPlayer<N> {
+    notes: N,
+    remaining: u32,
+    finished: bool,
+}
+
+impl<N: Iterator<Item = Note>> MelodyPlayer<N> {
+    pub const fn new(notes: N) -> Self {
+        Self {
+            notes,
+            remaining: 0,
+            finished: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Counts down the current note by `elapsed` milliseconds, returning the next note once it
+    /// is over.
+    fn advance(&mut self, elapsed: u32) -> Option<Note> {
+        self.remaining = self.remaining.saturating_sub(elapsed);
+        if self.remaining > 0 || self.finished {
+            return None;
+        }
+        let note = self.notes.next();
+        match note {
+            Some(n) => self.remaining = n.duration.to_millis().max(1),
+            None => self.finished = true,
+        }
+        note
+    }
+
+    /// Plays every remaining note, blocking until the last one has finished.
+    pub fn play<I2C: I2c, D: DelayNs>(
+        &mut self,
+        p18: &mut P18<I2C>,
+        delay: &mut D,
+    ) -> Result<(), Error<I2C::Error>> {
+        while let Some(note) = self.advance(u32::MAX) {
+            p18.play_note(note)?;
+            delay.delay_ms(note.duration.to_millis());
+        }
+        Ok(())
+    }
+
+    /// Starts the next note once the current one has played for its duration.  `elapsed` is
+    /// the time since the previous call.  Returns whether the melody is still playing.
+    pub fn tick<I2C: I2c>(
+        &mut self,
+        p18: &mut P18<I2C>,
+        elapsed: MillisDuration<u32>,
+    ) -> Result<bool, Error<I2C::Error>> {
+        if let Some(note) = self.advance(elapsed.to_millis()) {
+            p18.play_note(note)?;
+        }
+        Ok(!self.finished)
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::{vec, vec::Vec};
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use fugit::ExtU32;
+
+    use crate::p18::melody::{Alert, MelodyPlayer, Note, ParseError, Rtttl};
+    use crate::p18::P18;
+
+    #[test]
+    pub fn parse() {
+        let rtttl = Rtttl::parse("Intro:d=8,o=5,b=120:c,e,4g.,p,2c6,16a#4,h.5").unwrap();
+        assert_eq!(rtttl.name(), "Intro");
+        assert_eq!(
+            rtttl.notes().collect::<Vec<_>>(),
+            [
+                Note::tone(523, 250),
+                Note::tone(659, 250),
+                Note::tone(784, 750),
+                Note::rest(250),
+                Note::tone(1047, 1000),
+                Note::tone(466, 125),
+                Note::tone(988, 375),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn parse_defaults() {
+        let rtttl = Rtttl::parse("Beep::a, 8p ,").unwrap();
+        assert_eq!(rtttl.name(), "Beep");
+        assert_eq!(
+            rtttl.notes().collect::<Vec<_>>(),
+            [Note::tone(1760, 952), Note::rest(476)]
+        );
+        assert_eq!(Rtttl::parse("Empty:d=4:").unwrap().notes().count(), 0);
+    }
+
+    #[test]
+    pub fn parse_errors() {
+        assert_eq!(Rtttl::parse("c,d,e"), Err(ParseError::MissingSection));
+        assert_eq!(Rtttl::parse("x:d=3:c"), Err(ParseError::InvalidDefault));
+        assert_eq!(Rtttl::parse("x:o=9:c"), Err(ParseError::InvalidDefault));
+        assert_eq!(Rtttl::parse("x:b=0:c"), Err(ParseError::InvalidDefault));
+        assert_eq!(Rtttl::parse("x:b=1:c"), Err(ParseError::InvalidDefault));
+        assert_eq!(Rtttl::parse("x:b=3:c"), Err(ParseError::InvalidDefault));
+        assert!(Rtttl::parse("x:b=4:1c").is_ok());
+        assert_eq!(Rtttl::parse("x:b=4:1c."), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x:q=1:c"), Err(ParseError::InvalidDefault));
+        assert_eq!(Rtttl::parse("x::c,x"), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x::3c"), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x::c9"), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x::c5x"), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x::b#8"), Err(ParseError::InvalidNote));
+        assert_eq!(Rtttl::parse("x::c400000000"), Err(ParseError::InvalidNote));
+        assert_eq!(
+            Rtttl::parse("x::c99999999999"),
+            Err(ParseError::InvalidNote)
+        );
+        assert_eq!(
+            Rtttl::parse("x::99999999999c"),
+            Err(ParseError::InvalidNote)
+        );
+    }
+
+    fn tone(frequency: u16, duration: u16) -> I2cTransaction {
+        let [f1, f2] = frequency.to_be_bytes();
+        let [d1, d2] = duration.to_be_bytes();
+        I2cTransaction::write(0x5C, vec![0x05, f1, f2, d1, d2])
+    }
+
+    #[test]
+    pub fn play() {
+        let expectations = [tone(2000, 80), tone(0, 0), tone(2000, 80)];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p18 = P18 { i2c, address: 0x5C };
+
+        let mut player = MelodyPlayer::new(Alert::DoubleBeep.notes().iter().copied());
+        assert_eq!(player.play(&mut p18, &mut NoopDelay::new()), Ok(()));
+        assert!(player.is_finished());
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn tick() {
+        let expectations = [tone(1047, 100), tone(1568, 200)];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+        let mut p18 = P18 { i2c, address: 0x5C };
+
+        let mut player = MelodyPlayer::new(Alert::Success.notes().iter().copied());
+        assert_eq!(player.tick(&mut p18, 0.millis()), Ok(true));
+        assert_eq!(player.tick(&mut p18, 60.millis()), Ok(true));
+        assert_eq!(player.tick(&mut p18, 60.millis()), Ok(true));
+        assert_eq!(player.tick(&mut p18, 150.millis()), Ok(true));
+        assert_eq!(player.tick(&mut p18, 50.millis()), Ok(false));
+        assert_eq!(player.tick(&mut p18, 50.millis()), Ok(false));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn alerts() {
+        for alert in [
+            Alert::Beep,
+            Alert::DoubleBeep,
+            Alert::Success,
+            Alert::Failure,
+            Alert::Alarm,
+        ] {
+            assert!(!alert.notes().is_empty());
+        }
+        let mut player = MelodyPlayer::new(Alert::Alarm.notes().iter().copied().cycle());
+        assert_eq!(player.advance(0), Some(Note::tone(2637, 150)));
+        assert_eq!(player.advance(600), Some(Note::tone(1760, 150)));
+        assert_eq!(player.advance(450), Some(Note::tone(2637, 150)));
+        assert!(!player.is_finished());
+    }
+}
@@ -16,9 +16,11 @@ use crate::Driver;
 use core::num::TryFromIntError;
 use embedded_hal::i2c::I2c;
 use fugit::{ExtU32, Hertz, MillisDuration, RateExtU32};
+use num_enum::IntoPrimitive;
 
 const REG_STATUS: u8 = 0x01;
 const REG_TONE: u8 = 0x05;
+const REG_VOLUME: u8 = 0x06;
 const REG_SELF_TEST: u8 = 0x09;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -33,6 +35,35 @@ impl<E> From<E> for Error<E> {
     }
 }
 
+/// The firmware has no silent level, so use [`P18::no_tone`] to stop a tone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum Volume {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+}
+
+const STATUS_PLAYING: u8 = 0b0000_0001;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// A tone is currently sounding.
+    pub playing: bool,
+    /// The remaining status bits, which the firmware sets to report errors.  Zero when healthy.
+    /// The meaning of individual bits is not documented, so they are passed through as read.
+    pub errors: u8,
+}
+
+impl From<u8> for Status {
+    fn from(status: u8) -> Self {
+        Self {
+            playing: status & STATUS_PLAYING != 0,
+            errors: status & !STATUS_PLAYING,
+        }
+    }
+}
+
 pub struct P18<I2C> {
     i2c: I2C,
     address: u8,
@@ -79,11 +110,23 @@ impl<I2C: I2c> P18<I2C> {
         self.tone(0.Hz(), 0.millis())
     }
 
-    pub fn read_status(&mut self) -> Result<u8, I2C::Error> {
+    /// Starts playing `note`, or silences the buzzer for a rest.  Returns straight away.
+    pub fn play_note(&mut self, note: melody::Note) -> Result<(), Error<I2C::Error>> {
+        match note.frequency {
+            Some(frequency) => self.tone(frequency, note.duration),
+            None => self.no_tone(),
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: Volume) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[REG_VOLUME, volume.into()])
+    }
+
+    pub fn read_status(&mut self) -> Result<Status, I2C::Error> {
         let mut data: [u8; 1] = [0x00];
         self.i2c
             .write_read(self.address, &[REG_STATUS], &mut data)?;
-        Ok(data[0])
+        Ok(Status::from(data[0]))
     }
 
     pub fn self_test(&mut self) -> Result<bool, I2C::Error> {
@@ -109,7 +152,7 @@ mod test {
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
     use fugit::{ExtU32, RateExtU32};
 
-    use crate::p18::{Error, P18};
+    use crate::p18::{Error, Status, Volume, P18};
 
     #[test]
     pub fn new() {
@@ -178,7 +221,47 @@ mod test {
 
         let mut p18 = P18 { i2c, address: 0x5C };
 
-        assert_eq!(p18.read_status(), Ok(1));
+        assert_eq!(
+            p18.read_status(),
+            Ok(Status {
+                playing: true,
+                errors: 0
+            })
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_status_errors() {
+        let expectations = [I2cTransaction::write_read(0x5C, vec![0x01], vec![0x0C])];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p18 = P18 { i2c, address: 0x5C };
+
+        assert_eq!(
+            p18.read_status(),
+            Ok(Status {
+                playing: false,
+                errors: 0x0C
+            })
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_volume() {
+        let expectations = [
+            I2cTransaction::write(0x5C, vec![0x06, 0x00]),
+            I2cTransaction::write(0x5C, vec![0x06, 0x02]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p18 = P18 { i2c, address: 0x5C };
+
+        assert_eq!(p18.set_volume(Volume::Low), Ok(()));
+        assert_eq!(p18.set_volume(Volume::High), Ok(()));
         i2c_clone.done();
     }
 
@@ -208,4 +291,5 @@ mod test {
 }
 
 pub mod atmel;
+pub mod melody;
 pub mod whoami;
@@ -0,0 +1,127 @@
+//! Alarm settings for [`P19`](super::P19).  The alarm fires when every enabled field matches
+//! the calendar, so an alarm with only `minute` set fires once an hour.
+
+use super::datetime::{from_bcd, to_bcd, Weekday};
+use super::{decode_hour, encode_hour};
+
+/// The alarm field is ignored when this bit is set.
+const DISABLED: u8 = 0b1000_0000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmDay {
+    Weekday(Weekday),
+    /// Day of the month, `1..=31`.
+    Date(u8),
+}
+
+/// Fields set to `None` match any value.  An alarm with every field `None` never fires.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Alarm {
+    pub minute: Option<u8>,
+    /// In 24 hour time, regardless of the hour mode of the clock.
+    pub hour: Option<u8>,
+    pub day: Option<AlarmDay>,
+}
+
+impl Alarm {
+    /// Register values for minutes, hours and weekday or date, or `None` if a field is out of
+    /// range.
+    pub(super) fn to_registers(self, twelve_hour: bool) -> Option<[u8; 3]> {
+        let minute = match self.minute {
+            Some(m @ 0..=59) => to_bcd(m),
+            Some(_) => return None,
+            None => DISABLED,
+        };
+        let hour = match self.hour {
+            Some(h @ 0..=23) => encode_hour(h, twelve_hour),
+            Some(_) => return None,
+            None => DISABLED,
+        };
+        let day = match self.day {
+            Some(AlarmDay::Weekday(weekday)) => weekday.into(),
+            Some(AlarmDay::Date(d @ 1..=31)) => to_bcd(d),
+            Some(AlarmDay::Date(_)) => return None,
+            None => DISABLED,
+        };
+        Some([minute, hour, day])
+    }
+
+    /// `date` selects whether the last register holds a date rather than a weekday.
+    pub(super) fn from_registers(
+        registers: [u8; 3],
+        date: bool,
+        twelve_hour: bool,
+    ) -> Option<Self> {
+        let [minute, hour, day] = registers;
+        let enabled = |register: u8| register & DISABLED == 0;
+        Some(Self {
+            minute: if enabled(minute) {
+                Some(from_bcd(minute & 0x7F)?)
+            } else {
+                None
+            },
+            hour: if enabled(hour) {
+                Some(decode_hour(hour & 0x7F, twelve_hour)?)
+            } else {
+                None
+            },
+            day: match (enabled(day), date) {
+                (false, _) => None,
+                (true, true) => Some(AlarmDay::Date(from_bcd(day & 0x3F)?)),
+                (true, false) => Some(AlarmDay::Weekday(Weekday::try_from(day & 0x07).ok()?)),
+            },
+        })
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    use crate::p19::alarm::{Alarm, AlarmDay};
+    use crate::p19::datetime::Weekday;
+
+    #[test]
+    pub fn registers() {
+        let alarm = Alarm {
+            minute: Some(30),
+            hour: Some(18),
+            day: Some(AlarmDay::Weekday(Weekday::Friday)),
+        };
+        assert_eq!(alarm.to_registers(false), Some([0x30, 0x18, 0x05]));
+        assert_eq!(alarm.to_registers(true), Some([0x30, 0x26, 0x05]));
+        assert_eq!(
+            Alarm::from_registers([0x30, 0x26, 0x05], false, true),
+            Some(alarm)
+        );
+
+        let monthly = Alarm {
+            day: Some(AlarmDay::Date(31)),
+            ..Alarm::default()
+        };
+        assert_eq!(monthly.to_registers(false), Some([0x80, 0x80, 0x31]));
+        assert_eq!(
+            Alarm::from_registers([0x80, 0x80, 0x31], true, false),
+            Some(monthly)
+        );
+        assert_eq!(
+            Alarm::from_registers([0xD9, 0x80, 0x80], false, false),
+            Some(Alarm::default())
+        );
+    }
+
+    #[test]
+    pub fn out_of_range() {
+        let alarm = |minute, hour, day| Alarm { minute, hour, day }.to_registers(false);
+        assert_eq!(alarm(Some(60), None, None), None);
+        assert_eq!(alarm(None, Some(24), None), None);
+        assert_eq!(alarm(None, None, Some(AlarmDay::Date(0))), None);
+        assert_eq!(alarm(None, None, Some(AlarmDay::Date(32))), None);
+        assert_eq!(
+            Alarm::from_registers([0x5A, 0x80, 0x80], false, false),
+            None
+        );
+        assert_eq!(
+            Alarm::from_registers([0x80, 0x80, 0x07], false, false),
+            None
+        );
+    }
+}
@@ -0,0 +1,338 @@
+//! Calendar date and time as kept by the RV3028, which counts years 2000 to 2099.
+
+use core::fmt;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+const UNIX_2000: u32 = 946_684_800;
+const SECONDS_PER_DAY: u32 = 86_400;
+const FIRST_YEAR: u16 = 2000;
+const LAST_YEAR: u16 = 2099;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidDateTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum Weekday {
+    Sunday = 0,
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+}
+
+/// Every year from 2000 to 2099 divisible by 4 is a leap year.
+const fn days_in_year(year: u16) -> u32 {
+    if year.is_multiple_of(4) {
+        366
+    } else {
+        365
+    }
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+pub(crate) const fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4_u8) | (value % 10)
+}
+
+pub(crate) const fn from_bcd(bcd: u8) -> Option<u8> {
+    if bcd >> 4_u8 > 9 || bcd & 0x0F > 9 {
+        None
+    } else {
+        Some((bcd >> 4_u8) * 10 + (bcd & 0x0F))
+    }
+}
+
+/// A valid date and time between the start of 2000 and the end of 2099.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DateTime {
+    /// `month` and `day` count from 1 and `hour` is in 24 hour time.
+    pub const fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, InvalidDateTime> {
+        if year < FIRST_YEAR
+            || year > LAST_YEAR
+            || month < 1
+            || month > 12
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(InvalidDateTime);
+        }
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    #[must_use]
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    #[must_use]
+    pub const fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    #[must_use]
+    pub const fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    #[must_use]
+    pub const fn second(&self) -> u8 {
+        self.second
+    }
+
+    fn days_since_2000(self) -> u32 {
+        let years: u32 = (FIRST_YEAR..self.year).map(days_in_year).sum();
+        let months: u32 = (1..self.month)
+            .map(|month| u32::from(days_in_month(self.year, month)))
+            .sum();
+        years + months + u32::from(self.day) - 1
+    }
+
+    #[must_use]
+    pub fn weekday(self) -> Weekday {
+        match (self.days_since_2000() + 6) % 7 {
+            0 => Weekday::Sunday,
+            1 => Weekday::Monday,
+            2 => Weekday::Tuesday,
+            3 => Weekday::Wednesday,
+            4 => Weekday::Thursday,
+            5 => Weekday::Friday,
+            _ => Weekday::Saturday,
+        }
+    }
+
+    /// Seconds since 1970-01-01 00:00:00 UTC, treating this as UTC.
+    #[must_use]
+    pub fn to_unix(self) -> u32 {
+        UNIX_2000
+            + self.days_since_2000() * SECONDS_PER_DAY
+            + u32::from(self.hour) * 3600
+            + u32::from(self.minute) * 60
+            + u32::from(self.second)
+    }
+
+    /// Fails for times before 2000 or after 2099.
+    pub fn from_unix(unix_time: u32) -> Result<Self, InvalidDateTime> {
+        let since_2000 = unix_time.checked_sub(UNIX_2000).ok_or(InvalidDateTime)?;
+        let mut days = since_2000 / SECONDS_PER_DAY;
+        let seconds = since_2000 % SECONDS_PER_DAY;
+        let mut year = FIRST_YEAR;
+        while days >= days_in_year(year) {
+            days -= days_in_year(year);
+            year += 1;
+        }
+        let mut month = 1;
+        while days >= u32::from(days_in_month(year, month)) {
+            days -= u32::from(days_in_month(year, month));
+            month += 1;
+        }
+        let narrow = |value: u32| u8::try_from(value).map_err(|_| InvalidDateTime);
+        Self::new(
+            year,
+            month,
+            narrow(days + 1)?,
+            narrow(seconds / 3600)?,
+            narrow(seconds / 60 % 60)?,
+            narrow(seconds % 60)?,
+        )
+    }
+}
+
+/// ISO 8601, e.g. `2023-05-08T02:48:32`.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDateTime {
+    fn from(date_time: DateTime) -> Self {
+        chrono::NaiveDate::from_ymd_opt(
+            i32::from(date_time.year),
+            u32::from(date_time.month),
+            u32::from(date_time.day),
+        )
+        .and_then(|date| {
+            date.and_hms_opt(
+                u32::from(date_time.hour),
+                u32::from(date_time.minute),
+                u32::from(date_time.second),
+            )
+        })
+        .expect("DateTime is always a valid date")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for DateTime {
+    type Error = InvalidDateTime;
+
+    fn try_from(date_time: chrono::NaiveDateTime) -> Result<Self, Self::Error> {
+        use chrono::{Datelike, Timelike};
+        let narrow = |value: u32| u8::try_from(value).map_err(|_| InvalidDateTime);
+        Self::new(
+            u16::try_from(date_time.year()).map_err(|_| InvalidDateTime)?,
+            narrow(date_time.month())?,
+            narrow(date_time.day())?,
+            narrow(date_time.hour())?,
+            narrow(date_time.minute())?,
+            narrow(date_time.second())?,
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<DateTime> for time::PrimitiveDateTime {
+    fn from(date_time: DateTime) -> Self {
+        time::Month::try_from(date_time.month)
+            .and_then(|month| {
+                time::Date::from_calendar_date(i32::from(date_time.year), month, date_time.day)
+            })
+            .and_then(|date| date.with_hms(date_time.hour, date_time.minute, date_time.second))
+            .expect("DateTime is always a valid date")
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::PrimitiveDateTime> for DateTime {
+    type Error = InvalidDateTime;
+
+    fn try_from(date_time: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+        Self::new(
+            u16::try_from(date_time.year()).map_err(|_| InvalidDateTime)?,
+            date_time.month().into(),
+            date_time.day(),
+            date_time.hour(),
+            date_time.minute(),
+            date_time.second(),
+        )
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::format;
+
+    use crate::p19::datetime::{from_bcd, to_bcd, DateTime, InvalidDateTime, Weekday};
+
+    #[test]
+    pub fn bcd() {
+        assert_eq!(to_bcd(59), 0x59);
+        assert_eq!(from_bcd(0x59), Some(59));
+        assert_eq!(from_bcd(0x5A), None);
+        assert_eq!(from_bcd(0xA0), None);
+    }
+
+    #[test]
+    pub fn new() {
+        assert!(DateTime::new(2024, 2, 29, 23, 59, 59).is_ok());
+        assert_eq!(DateTime::new(2023, 2, 29, 0, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(2023, 4, 31, 0, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(2023, 13, 1, 0, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(2023, 1, 0, 0, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(2023, 1, 1, 24, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(1999, 12, 31, 0, 0, 0), Err(InvalidDateTime));
+        assert_eq!(DateTime::new(2100, 1, 1, 0, 0, 0), Err(InvalidDateTime));
+    }
+
+    #[test]
+    pub fn unix() {
+        let date_time = DateTime::new(2023, 5, 8, 2, 48, 32).unwrap();
+        assert_eq!(date_time.to_unix(), 1_683_514_112);
+        assert_eq!(DateTime::from_unix(1_683_514_112), Ok(date_time));
+        assert_eq!(format!("{date_time}"), "2023-05-08T02:48:32");
+
+        let first = DateTime::new(2000, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(first.to_unix(), 946_684_800);
+        assert_eq!(DateTime::from_unix(946_684_800), Ok(first));
+        assert_eq!(DateTime::from_unix(946_684_799), Err(InvalidDateTime));
+
+        let leap_day = DateTime::new(2024, 2, 29, 12, 0, 0).unwrap();
+        assert_eq!(DateTime::from_unix(leap_day.to_unix()), Ok(leap_day));
+
+        let last = DateTime::new(2099, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(last.to_unix(), 4_102_444_799);
+        assert_eq!(DateTime::from_unix(4_102_444_799), Ok(last));
+        assert_eq!(DateTime::from_unix(4_102_444_800), Err(InvalidDateTime));
+    }
+
+    #[test]
+    pub fn weekday() {
+        let weekday =
+            |year, month, day| DateTime::new(year, month, day, 0, 0, 0).unwrap().weekday();
+        assert_eq!(weekday(2000, 1, 1), Weekday::Saturday);
+        assert_eq!(weekday(2023, 5, 8), Weekday::Monday);
+        assert_eq!(weekday(2024, 2, 29), Weekday::Thursday);
+        assert_eq!(weekday(2099, 12, 31), Weekday::Thursday);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    pub fn chrono() {
+        let date_time = DateTime::new(2023, 5, 8, 2, 48, 32).unwrap();
+        let naive = chrono::NaiveDateTime::from(date_time);
+        assert_eq!(naive.and_utc().timestamp(), 1_683_514_112);
+        assert_eq!(DateTime::try_from(naive), Ok(date_time));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    pub fn time() {
+        let date_time = DateTime::new(2023, 5, 8, 2, 48, 32).unwrap();
+        let primitive = time::PrimitiveDateTime::from(date_time);
+        assert_eq!(primitive.assume_utc().unix_timestamp(), 1_683_514_112);
+        assert_eq!(DateTime::try_from(primitive), Ok(date_time));
+    }
+}
@@ -13,13 +13,189 @@
 //! [Datasheet]: https://www.microcrystal.com/fileadmin/Media/Products/RTC/App.Manual/RV-3028-C7_App-Manual.pdf
 
 use crate::Driver;
+use alarm::Alarm;
+use datetime::{from_bcd, to_bcd, DateTime};
 use embedded_hal::i2c::I2c;
 use num_enum::IntoPrimitive;
 
+const REG_SECONDS: u8 = 0x00;
+const REG_HOURS: u8 = 0x02;
+const REG_MINUTES_ALARM: u8 = 0x07;
+const REG_TIMER_VALUE: u8 = 0x0A;
+const REG_TIMER_STATUS: u8 = 0x0C;
 const REG_STATUS: u8 = 0x0E;
+const REG_CONTROL_1: u8 = 0x0F;
+const REG_CONTROL_2: u8 = 0x10;
+const REG_EVENT_CONTROL: u8 = 0x13;
+const REG_COUNT_TIMESTAMP: u8 = 0x14;
 const REG_UNIX: u8 = 0x1B;
+const REG_USER_RAM: u8 = 0x1F;
+const REG_EEPROM_ADDRESS: u8 = 0x25;
+const REG_EEPROM_DATA: u8 = 0x26;
+const REG_EEPROM_COMMAND: u8 = 0x27;
+const REG_EEPROM_CLKOUT: u8 = 0x35;
+const REG_EEPROM_OFFSET: u8 = 0x36;
 const REG_EEPROM_BACKUP: u8 = 0x37;
 
+const STATUS_EEPROM_BUSY: u8 = 0b1000_0000;
+const STATUS_BACKUP_SWITCH: u8 = 0b0010_0000;
+const STATUS_UPDATE: u8 = 0b0001_0000;
+const STATUS_TIMER: u8 = 0b0000_1000;
+const STATUS_ALARM: u8 = 0b0000_0100;
+const STATUS_EVENT: u8 = 0b0000_0010;
+const CONTROL_1_TIMER_REPEAT: u8 = 0b1000_0000;
+const CONTROL_1_DATE_ALARM: u8 = 0b0010_0000;
+const CONTROL_1_MINUTE_UPDATE: u8 = 0b0001_0000;
+const CONTROL_1_EEPROM_REFRESH_DISABLE: u8 = 0b0000_1000;
+const CONTROL_1_TIMER_ENABLE: u8 = 0b0000_0100;
+const CONTROL_1_TIMER_FREQUENCY: u8 = 0b0000_0011;
+const CONTROL_2_TIMESTAMP_ENABLE: u8 = 0b1000_0000;
+const CONTROL_2_UPDATE_INTERRUPT: u8 = 0b0010_0000;
+const CONTROL_2_TIMER_INTERRUPT: u8 = 0b0001_0000;
+const CONTROL_2_ALARM_INTERRUPT: u8 = 0b0000_1000;
+const CONTROL_2_EVENT_INTERRUPT: u8 = 0b0000_0100;
+const CONTROL_2_12_HOUR: u8 = 0b0000_0010;
+const HOURS_PM: u8 = 0b0010_0000;
+const EVENT_HIGH: u8 = 0b0100_0000;
+const EVENT_FILTER: u8 = 0b0011_0000;
+const EVENT_TIMESTAMP_RESET: u8 = 0b0000_0100;
+const EVENT_TIMESTAMP_OVERWRITE: u8 = 0b0000_0010;
+const EVENT_TIMESTAMP_SOURCE: u8 = 0b0000_0001;
+const CLKOUT_ENABLE: u8 = 0b1000_0000;
+const CLKOUT_FREQUENCY: u8 = 0b0000_0111;
+const MAX_TIMER_VALUE: u16 = 0x0FFF;
+const BACKUP_OFFSET_LSB: u8 = 0b1000_0000;
+const EEPROM_FIRST_COMMAND: u8 = 0x00;
+const EEPROM_UPDATE: u8 = 0x11;
+const EEPROM_REFRESH: u8 = 0x12;
+const EEPROM_WRITE_BYTE: u8 = 0x21;
+const EEPROM_READ_BYTE: u8 = 0x22;
+/// Status reads to wait for an EEPROM command, comfortably longer than the slowest command.
+const EEPROM_BUSY_POLLS: u16 = 10_000;
+
+/// Bytes of user EEPROM, addressed from 0.
+pub const USER_EEPROM_SIZE: u8 = 43;
+/// Frequency adjustment of one step of [`P19::set_offset`].
+pub const OFFSET_PPM_PER_STEP: f32 = 0.9537;
+const MIN_OFFSET: i16 = -256;
+const MAX_OFFSET: i16 = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    I2cError(E),
+    ArgumentError,
+    /// The calendar registers do not hold a valid date, e.g. after a power on reset.
+    InvalidData,
+    /// The EEPROM stayed busy for too long.
+    Timeout,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Self::I2cError(error)
+    }
+}
+
+/// Encodes 24 hour time for the hours register.
+const fn encode_hour(hour: u8, twelve_hour: bool) -> u8 {
+    if !twelve_hour {
+        return to_bcd(hour);
+    }
+    let pm = if hour >= 12 { HOURS_PM } else { 0 };
+    match hour % 12 {
+        0 => pm | to_bcd(12),
+        h => pm | to_bcd(h),
+    }
+}
+
+/// Decodes the hours register into 24 hour time.
+const fn decode_hour(register: u8, twelve_hour: bool) -> Option<u8> {
+    if !twelve_hour {
+        return from_bcd(register & 0b0011_1111);
+    }
+    match from_bcd(register & 0b0001_1111) {
+        Some(h @ 1..=12) if register & HOURS_PM == 0 => Some(h % 12),
+        Some(h @ 1..=12) => Some(h % 12 + 12),
+        _ => None,
+    }
+}
+
+/// Countdown timer clock.  Each tick decrements the timer value by one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum TimerFrequency {
+    Hz4096 = 0,
+    Hz64 = 1,
+    Hz1 = 2,
+    OnePerMinute = 3,
+}
+
+/// How often the periodic time update flag is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateInterval {
+    Second,
+    Minute,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum ClockOutput {
+    Hz32768 = 0,
+    Hz8192 = 1,
+    Hz1024 = 2,
+    Hz64 = 3,
+    Hz32 = 4,
+    Hz1 = 5,
+    /// A pulse each time the countdown timer reaches zero.
+    Timer = 6,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventEdge {
+    Falling,
+    Rising,
+}
+
+/// How long the EVI pin must hold its new level before an event is registered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum EventFilter {
+    None = 0b0000_0000,
+    Ms3_9 = 0b0001_0000,
+    Ms15_6 = 0b0010_0000,
+    Ms125 = 0b0011_0000,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampSource {
+    /// An edge on the EVI pin.
+    Event,
+    /// Switching over to the backup supply.
+    BackupSwitchover,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Events since the timestamp was last reset, saturating at 255.
+    pub count: u8,
+    /// Time of the first or the most recent event, depending on whether overwriting is enabled.
+    pub date_time: DateTime,
+}
+
+/// Decodes seconds, minutes, hours, date, month and year registers.
+fn decode_datetime(registers: [u8; 6], twelve_hour: bool) -> Option<DateTime> {
+    let [second, minute, hour, day, month, year] = registers;
+    DateTime::new(
+        2000 + u16::from(from_bcd(year)?),
+        from_bcd(month)?,
+        from_bcd(day)?,
+        decode_hour(hour, twelve_hour)?,
+        from_bcd(minute)?,
+        from_bcd(second & 0b0111_1111)?,
+    )
+    .ok()
+}
+
 #[derive(IntoPrimitive)]
 #[repr(u8)]
 pub enum TrickleResistance {
@@ -74,7 +250,7 @@ impl<I2C: I2c> P19<I2C> {
             self.address,
             &[
                 REG_EEPROM_BACKUP,
-                ((data[0] | 0x80) & 0b1111_1100)
+                (data[0] & 0b1111_1100)
                     | <TrickleResistance as core::convert::Into<u8>>::into(trickle_resistance),
             ],
         )?;
@@ -113,18 +289,396 @@ impl<I2C: I2c> P19<I2C> {
         Ok(())
     }
 
-    pub fn check_alarm(&mut self) -> Result<bool, I2C::Error> {
+    fn read_register(&mut self, register: u8) -> Result<u8, I2C::Error> {
         let mut data: [u8; 1] = [0];
+        self.i2c.write_read(self.address, &[register], &mut data)?;
+        Ok(data[0])
+    }
+
+    pub fn is_12_hour_mode(&mut self) -> Result<bool, I2C::Error> {
+        Ok(self.read_register(REG_CONTROL_2)? & CONTROL_2_12_HOUR != 0)
+    }
+
+    /// Switches the hours register between 12 and 24 hour format, converting the current hour
+    /// so that the time is unchanged.  [`P19::get_datetime`] always returns 24 hour time.
+    pub fn set_12_hour_mode(&mut self, twelve_hour: bool) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(REG_CONTROL_2)?;
+        let hour = decode_hour(
+            self.read_register(REG_HOURS)?,
+            control & CONTROL_2_12_HOUR != 0,
+        )
+        .ok_or(Error::InvalidData)?;
+        let new_control = if twelve_hour {
+            control | CONTROL_2_12_HOUR
+        } else {
+            control & !CONTROL_2_12_HOUR
+        };
+        self.i2c
+            .write(self.address, &[REG_CONTROL_2, new_control])?;
         self.i2c
-            .write_read(self.address, &[REG_STATUS], &mut data)?;
-        if (data[0] & 0b0000_0100) == 0 {
+            .write(self.address, &[REG_HOURS, encode_hour(hour, twelve_hour)])?;
+        Ok(())
+    }
+
+    /// Reads the calendar registers.  These count independently of the UNIX time counter.
+    pub fn get_datetime(&mut self) -> Result<DateTime, Error<I2C::Error>> {
+        let twelve_hour = self.is_12_hour_mode()?;
+        let mut data: [u8; 7] = [0; 7];
+        self.i2c
+            .write_read(self.address, &[REG_SECONDS], &mut data)?;
+        let [second, minute, hour, _weekday, day, month, year] = data;
+        decode_datetime([second, minute, hour, day, month, year], twelve_hour)
+            .ok_or(Error::InvalidData)
+    }
+
+    /// Sets the calendar registers, including the weekday.  The UNIX time counter is left as
+    /// is.
+    pub fn set_datetime(&mut self, date_time: DateTime) -> Result<(), Error<I2C::Error>> {
+        let twelve_hour = self.is_12_hour_mode()?;
+        let year = u8::try_from(date_time.year() - 2000).map_err(|_| Error::ArgumentError)?;
+        self.i2c.write(
+            self.address,
+            &[
+                REG_SECONDS,
+                to_bcd(date_time.second()),
+                to_bcd(date_time.minute()),
+                encode_hour(date_time.hour(), twelve_hour),
+                date_time.weekday().into(),
+                to_bcd(date_time.day()),
+                to_bcd(date_time.month()),
+                to_bcd(year),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn update_register(&mut self, register: u8, mask: u8, value: bool) -> Result<(), I2C::Error> {
+        let current = self.read_register(register)?;
+        let updated = if value {
+            current | mask
+        } else {
+            current & !mask
+        };
+        self.i2c.write(self.address, &[register, updated])
+    }
+
+    /// Sets the alarm and clears any alarm that has already fired.  Use
+    /// [`P19::set_alarm_interrupt`] to also signal the alarm on the INT pin.
+    pub fn set_alarm(&mut self, alarm: Alarm) -> Result<(), Error<I2C::Error>> {
+        let twelve_hour = self.is_12_hour_mode()?;
+        let [minute, hour, day] = alarm
+            .to_registers(twelve_hour)
+            .ok_or(Error::ArgumentError)?;
+        self.update_register(
+            REG_CONTROL_1,
+            CONTROL_1_DATE_ALARM,
+            matches!(alarm.day, Some(alarm::AlarmDay::Date(_))),
+        )?;
+        self.i2c
+            .write(self.address, &[REG_MINUTES_ALARM, minute, hour, day])?;
+        self.update_register(REG_STATUS, STATUS_ALARM, false)?;
+        Ok(())
+    }
+
+    pub fn get_alarm(&mut self) -> Result<Alarm, Error<I2C::Error>> {
+        let twelve_hour = self.is_12_hour_mode()?;
+        let date = self.read_register(REG_CONTROL_1)? & CONTROL_1_DATE_ALARM != 0;
+        let mut data: [u8; 3] = [0; 3];
+        self.i2c
+            .write_read(self.address, &[REG_MINUTES_ALARM], &mut data)?;
+        Alarm::from_registers(data, date, twelve_hour).ok_or(Error::InvalidData)
+    }
+
+    /// Whether the alarm pulls the open drain INT pin low.  INT is released once the alarm flag
+    /// is cleared by [`P19::check_alarm`].
+    pub fn set_alarm_interrupt(&mut self, enabled: bool) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_2, CONTROL_2_ALARM_INTERRUPT, enabled)
+    }
+
+    /// Reads a status flag, clearing it if set.
+    fn take_flag(&mut self, mask: u8) -> Result<bool, I2C::Error> {
+        let status = self.read_register(REG_STATUS)?;
+        if status & mask == 0 {
             Ok(false)
         } else {
             self.i2c
-                .write(self.address, &[REG_STATUS, data[0] & 0b1111_1011])?;
+                .write(self.address, &[REG_STATUS, status & !mask])?;
             Ok(true)
         }
     }
+
+    pub fn check_alarm(&mut self) -> Result<bool, I2C::Error> {
+        self.take_flag(STATUS_ALARM)
+    }
+
+    /// Starts the countdown timer from `value`, which must be in `1..=4095`.  The timer flag is
+    /// set when it reaches zero, after which it either reloads `value` or stops.
+    pub fn start_timer(
+        &mut self,
+        frequency: TimerFrequency,
+        value: u16,
+        repeat: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !(1..=MAX_TIMER_VALUE).contains(&value) {
+            return Err(Error::ArgumentError);
+        }
+        self.stop_timer()?;
+        let [low, high] = value.to_le_bytes();
+        self.i2c
+            .write(self.address, &[REG_TIMER_VALUE, low, high])?;
+        let control = self.read_register(REG_CONTROL_1)?
+            & !(CONTROL_1_TIMER_REPEAT | CONTROL_1_TIMER_FREQUENCY);
+        let repeat_bit = if repeat { CONTROL_1_TIMER_REPEAT } else { 0 };
+        self.i2c.write(
+            self.address,
+            &[
+                REG_CONTROL_1,
+                control | repeat_bit | CONTROL_1_TIMER_ENABLE | u8::from(frequency),
+            ],
+        )?;
+        self.take_flag(STATUS_TIMER)?;
+        Ok(())
+    }
+
+    pub fn stop_timer(&mut self) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_1, CONTROL_1_TIMER_ENABLE, false)
+    }
+
+    /// Ticks left before the countdown timer reaches zero.
+    pub fn timer_remaining(&mut self) -> Result<u16, I2C::Error> {
+        let mut data: [u8; 2] = [0; 2];
+        self.i2c
+            .write_read(self.address, &[REG_TIMER_STATUS], &mut data)?;
+        Ok(u16::from_le_bytes(data) & MAX_TIMER_VALUE)
+    }
+
+    pub fn check_timer(&mut self) -> Result<bool, I2C::Error> {
+        self.take_flag(STATUS_TIMER)
+    }
+
+    /// Whether the countdown timer pulls the INT pin low.
+    pub fn set_timer_interrupt(&mut self, enabled: bool) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_2, CONTROL_2_TIMER_INTERRUPT, enabled)
+    }
+
+    pub fn set_update_interval(&mut self, interval: UpdateInterval) -> Result<(), I2C::Error> {
+        self.update_register(
+            REG_CONTROL_1,
+            CONTROL_1_MINUTE_UPDATE,
+            interval == UpdateInterval::Minute,
+        )
+    }
+
+    /// Whether the periodic time update pulls the INT pin low.
+    pub fn set_update_interrupt(&mut self, enabled: bool) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_2, CONTROL_2_UPDATE_INTERRUPT, enabled)
+    }
+
+    pub fn check_update(&mut self) -> Result<bool, I2C::Error> {
+        self.take_flag(STATUS_UPDATE)
+    }
+
+    /// Sets which edge of the EVI pin is an event and how long it must be held.
+    pub fn configure_event(
+        &mut self,
+        edge: EventEdge,
+        filter: EventFilter,
+    ) -> Result<(), I2C::Error> {
+        let control = self.read_register(REG_EVENT_CONTROL)? & !(EVENT_HIGH | EVENT_FILTER);
+        let edge_bit = if edge == EventEdge::Rising {
+            EVENT_HIGH
+        } else {
+            0
+        };
+        self.i2c.write(
+            self.address,
+            &[REG_EVENT_CONTROL, control | edge_bit | u8::from(filter)],
+        )
+    }
+
+    /// Whether an EVI event pulls the INT pin low.
+    pub fn set_event_interrupt(&mut self, enabled: bool) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_2, CONTROL_2_EVENT_INTERRUPT, enabled)
+    }
+
+    pub fn check_event(&mut self) -> Result<bool, I2C::Error> {
+        self.take_flag(STATUS_EVENT)
+    }
+
+    /// Clears any previous timestamp and starts recording `source`.  With `overwrite` the
+    /// timestamp holds the most recent event, otherwise the first.
+    pub fn enable_timestamp(
+        &mut self,
+        source: TimestampSource,
+        overwrite: bool,
+    ) -> Result<(), I2C::Error> {
+        self.disable_timestamp()?;
+        let control = self.read_register(REG_EVENT_CONTROL)?
+            & !(EVENT_TIMESTAMP_OVERWRITE | EVENT_TIMESTAMP_SOURCE);
+        let overwrite_bit = if overwrite {
+            EVENT_TIMESTAMP_OVERWRITE
+        } else {
+            0
+        };
+        let source_bit = if source == TimestampSource::BackupSwitchover {
+            EVENT_TIMESTAMP_SOURCE
+        } else {
+            0
+        };
+        self.i2c.write(
+            self.address,
+            &[
+                REG_EVENT_CONTROL,
+                control | EVENT_TIMESTAMP_RESET | overwrite_bit | source_bit,
+            ],
+        )?;
+        self.update_register(REG_CONTROL_2, CONTROL_2_TIMESTAMP_ENABLE, true)
+    }
+
+    pub fn disable_timestamp(&mut self) -> Result<(), I2C::Error> {
+        self.update_register(REG_CONTROL_2, CONTROL_2_TIMESTAMP_ENABLE, false)
+    }
+
+    /// The recorded timestamp, or `None` if nothing has happened since it was enabled.
+    pub fn get_timestamp(&mut self) -> Result<Option<Timestamp>, Error<I2C::Error>> {
+        let twelve_hour = self.is_12_hour_mode()?;
+        let mut data: [u8; 7] = [0; 7];
+        self.i2c
+            .write_read(self.address, &[REG_COUNT_TIMESTAMP], &mut data)?;
+        let [count, second, minute, hour, day, month, year] = data;
+        if count == 0 {
+            return Ok(None);
+        }
+        let date_time = decode_datetime([second, minute, hour, day, month, year], twelve_hour)
+            .ok_or(Error::InvalidData)?;
+        Ok(Some(Timestamp { count, date_time }))
+    }
+
+    /// Whether the clock has switched to its backup supply since the flag was last checked.
+    pub fn check_battery_switchover(&mut self) -> Result<bool, I2C::Error> {
+        self.take_flag(STATUS_BACKUP_SWITCH)
+    }
+
+    /// Selects the signal on the CLKOUT pin, or holds it low when `None`.  This is an EEPROM
+    /// backed setting that is only changed in RAM.
+    pub fn set_clock_output(&mut self, output: Option<ClockOutput>) -> Result<(), I2C::Error> {
+        let clkout = self.read_register(REG_EEPROM_CLKOUT)? & !(CLKOUT_ENABLE | CLKOUT_FREQUENCY);
+        let value = output.map_or(clkout, |frequency| {
+            clkout | CLKOUT_ENABLE | u8::from(frequency)
+        });
+        self.i2c.write(self.address, &[REG_EEPROM_CLKOUT, value])
+    }
+
+    fn wait_for_eeprom(&mut self) -> Result<(), Error<I2C::Error>> {
+        for _ in 0..EEPROM_BUSY_POLLS {
+            if self.read_register(REG_STATUS)? & STATUS_EEPROM_BUSY == 0 {
+                return Ok(());
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    fn eeprom_command(&mut self, command: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write(self.address, &[REG_EEPROM_COMMAND, EEPROM_FIRST_COMMAND])?;
+        self.i2c
+            .write(self.address, &[REG_EEPROM_COMMAND, command])?;
+        self.wait_for_eeprom()
+    }
+
+    /// Runs `commands` with the automatic EEPROM refresh disabled, as the RV3028 requires,
+    /// re-enabling it afterwards even if they fail.
+    fn with_eeprom<T>(
+        &mut self,
+        commands: impl FnOnce(&mut Self) -> Result<T, Error<I2C::Error>>,
+    ) -> Result<T, Error<I2C::Error>> {
+        self.update_register(REG_CONTROL_1, CONTROL_1_EEPROM_REFRESH_DISABLE, true)?;
+        let result = self.wait_for_eeprom().and_then(|()| commands(self));
+        self.update_register(REG_CONTROL_1, CONTROL_1_EEPROM_REFRESH_DISABLE, false)?;
+        result
+    }
+
+    /// Saves the configuration registers, i.e. backup switchover, trickle charger, CLKOUT and
+    /// offset, to EEPROM so they survive a loss of all power.
+    pub fn update_eeprom(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.with_eeprom(|p19| p19.eeprom_command(EEPROM_UPDATE))
+    }
+
+    /// Discards unsaved configuration changes by reloading them from EEPROM.
+    pub fn refresh_eeprom(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.with_eeprom(|p19| p19.eeprom_command(EEPROM_REFRESH))
+    }
+
+    fn check_user_eeprom(address: u8, len: usize) -> Result<(), Error<I2C::Error>> {
+        if usize::from(address) + len > usize::from(USER_EEPROM_SIZE) {
+            return Err(Error::ArgumentError);
+        }
+        Ok(())
+    }
+
+    /// Fills `buffer` from user EEPROM starting at `address`.
+    pub fn read_user_eeprom(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        Self::check_user_eeprom(address, buffer.len())?;
+        self.with_eeprom(|p19| {
+            for (byte_address, byte) in (address..).zip(buffer.iter_mut()) {
+                p19.i2c
+                    .write(p19.address, &[REG_EEPROM_ADDRESS, byte_address])?;
+                p19.eeprom_command(EEPROM_READ_BYTE)?;
+                *byte = p19.read_register(REG_EEPROM_DATA)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Writes `data` to user EEPROM starting at `address`.  Each byte takes around 16 ms.
+    pub fn write_user_eeprom(&mut self, address: u8, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        Self::check_user_eeprom(address, data.len())?;
+        self.with_eeprom(|p19| {
+            for (byte_address, byte) in (address..).zip(data) {
+                p19.i2c
+                    .write(p19.address, &[REG_EEPROM_ADDRESS, byte_address, *byte])?;
+                p19.eeprom_command(EEPROM_WRITE_BYTE)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Two bytes of RAM kept while the clock runs, including on the backup supply.
+    pub fn read_user_ram(&mut self) -> Result<[u8; 2], I2C::Error> {
+        let mut data: [u8; 2] = [0; 2];
+        self.i2c
+            .write_read(self.address, &[REG_USER_RAM], &mut data)?;
+        Ok(data)
+    }
+
+    pub fn write_user_ram(&mut self, data: [u8; 2]) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[REG_USER_RAM, data[0], data[1]])
+    }
+
+    /// The frequency offset in steps of [`OFFSET_PPM_PER_STEP`].
+    pub fn get_offset(&mut self) -> Result<i16, I2C::Error> {
+        let high = self.read_register(REG_EEPROM_OFFSET)?;
+        let low = self.read_register(REG_EEPROM_BACKUP)? & BACKUP_OFFSET_LSB;
+        // Shifting the 9 bit value to the top of an i16 and back sign extends it.
+        Ok(i16::from_be_bytes([high, low]) >> 7_u8)
+    }
+
+    /// Trims the clock by `steps` of [`OFFSET_PPM_PER_STEP`], from -256 to 255.  A positive offset
+    /// speeds the clock up.  Call [`P19::update_eeprom`] to keep it after power loss.
+    pub fn set_offset(&mut self, steps: i16) -> Result<(), Error<I2C::Error>> {
+        if !(MIN_OFFSET..=MAX_OFFSET).contains(&steps) {
+            return Err(Error::ArgumentError);
+        }
+        let [high, low] = (steps << 7_u8).to_be_bytes();
+        self.i2c.write(self.address, &[REG_EEPROM_OFFSET, high])?;
+        self.update_register(REG_EEPROM_BACKUP, BACKUP_OFFSET_LSB, low != 0)?;
+        Ok(())
+    }
 }
 
 #[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
@@ -136,7 +690,12 @@ mod test {
     extern crate embedded_hal_mock;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p19::{TrickleResistance, P19};
+    use crate::p19::alarm::{Alarm, AlarmDay};
+    use crate::p19::datetime::{DateTime, Weekday};
+    use crate::p19::{
+        ClockOutput, Error, EventEdge, EventFilter, TimerFrequency, Timestamp, TimestampSource,
+        TrickleResistance, UpdateInterval, P19,
+    };
 
     #[test]
     pub fn new() {
@@ -144,7 +703,7 @@ mod test {
             I2cTransaction::write_read(0x52, vec![0x37], vec![0x19]),
             I2cTransaction::write(0x52, vec![0x37, 0x15]),
             I2cTransaction::write_read(0x52, vec![0x37], vec![0x00]),
-            I2cTransaction::write(0x52, vec![0x37, 0x80]),
+            I2cTransaction::write(0x52, vec![0x37, 0x00]),
             I2cTransaction::write_read(0x52, vec![0x37], vec![0x00]),
             I2cTransaction::write(0x52, vec![0x37, 0x20]),
         ];
@@ -190,7 +749,7 @@ mod test {
     pub fn config_trickle_charger() {
         let expectations = [
             I2cTransaction::write_read(0x52, vec![0x37], vec![0x00]),
-            I2cTransaction::write(0x52, vec![0x37, 0x83]),
+            I2cTransaction::write(0x52, vec![0x37, 0x03]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
@@ -204,6 +763,38 @@ mod test {
         i2c_clone.done();
     }
 
+    #[test]
+    pub fn init_keeps_offset() {
+        let expectations = [
+            I2cTransaction::write(0x52, vec![0x36, 0xFD]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x37, 0x80]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x80]),
+            I2cTransaction::write(0x52, vec![0x37, 0x84]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x84]),
+            I2cTransaction::write(0x52, vec![0x37, 0x84]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x84]),
+            I2cTransaction::write(0x52, vec![0x37, 0xA4]),
+            I2cTransaction::write_read(0x52, vec![0x36], vec![0xFD]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0xA4]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0xA4]),
+            I2cTransaction::write(0x52, vec![0x37, 0xA6]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut uninitialized = P19 { i2c, address: 0x52 };
+        assert_eq!(uninitialized.set_offset(-5), Ok(()));
+        let mut p19 = uninitialized.init().unwrap();
+        assert_eq!(p19.get_offset(), Ok(-5));
+
+        assert_eq!(
+            p19.config_trickle_charger(TrickleResistance::Resistance9kΩ),
+            Ok(())
+        );
+        i2c_clone.done();
+    }
+
     #[test]
     pub fn set_trickle_charger_true() {
         let expectations = [
@@ -295,6 +886,528 @@ mod test {
         assert_eq!(p19.check_alarm(), Ok(false));
         i2c_clone.done();
     }
+
+    #[test]
+    pub fn get_datetime_24_hour() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x00],
+                vec![0x32, 0x48, 0x14, 0x01, 0x08, 0x05, 0x23],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(
+            p19.get_datetime(),
+            Ok(DateTime::new(2023, 5, 8, 14, 48, 32).unwrap())
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn get_datetime_12_hour() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x00],
+                vec![0x32, 0x48, 0x22, 0x01, 0x08, 0x05, 0x23],
+            ),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x00],
+                vec![0x00, 0x00, 0x12, 0x01, 0x08, 0x05, 0x23],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(
+            p19.get_datetime(),
+            Ok(DateTime::new(2023, 5, 8, 14, 48, 32).unwrap())
+        );
+        assert_eq!(
+            p19.get_datetime(),
+            Ok(DateTime::new(2023, 5, 8, 0, 0, 0).unwrap())
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn get_datetime_invalid() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x00],
+                vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.get_datetime(), Err(Error::InvalidData));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_datetime() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x00, 0x32, 0x48, 0x14, 0x01, 0x08, 0x05, 0x23]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write(0x52, vec![0x00, 0x32, 0x48, 0x22, 0x01, 0x08, 0x05, 0x23]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        let date_time = DateTime::new(2023, 5, 8, 14, 48, 32).unwrap();
+        assert_eq!(p19.set_datetime(date_time), Ok(()));
+        assert_eq!(p19.set_datetime(date_time), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_12_hour_mode() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x02], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x02]),
+            I2cTransaction::write(0x52, vec![0x02, 0x12]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write_read(0x52, vec![0x02], vec![0x31]),
+            I2cTransaction::write(0x52, vec![0x10, 0x00]),
+            I2cTransaction::write(0x52, vec![0x02, 0x23]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.set_12_hour_mode(true), Ok(()));
+        assert_eq!(p19.set_12_hour_mode(false), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_alarm() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x20]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+            I2cTransaction::write(0x52, vec![0x07, 0x30, 0x06, 0x01]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0b0000_0101]),
+            I2cTransaction::write(0x52, vec![0x0E, 0b0000_0001]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x20]),
+            I2cTransaction::write(0x52, vec![0x07, 0x80, 0x80, 0x15]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(
+            p19.set_alarm(Alarm {
+                minute: Some(30),
+                hour: Some(6),
+                day: Some(AlarmDay::Weekday(Weekday::Monday)),
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            p19.set_alarm(Alarm {
+                day: Some(AlarmDay::Date(15)),
+                ..Alarm::default()
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            p19.set_alarm(Alarm {
+                minute: Some(60),
+                ..Alarm::default()
+            }),
+            Err(Error::ArgumentError)
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn get_alarm() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x20]),
+            I2cTransaction::write_read(0x52, vec![0x07], vec![0x45, 0x27, 0x80]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(
+            p19.get_alarm(),
+            Ok(Alarm {
+                minute: Some(45),
+                hour: Some(19),
+                day: None,
+            })
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_alarm_interrupt() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x02]),
+            I2cTransaction::write(0x52, vec![0x10, 0x0A]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x0A]),
+            I2cTransaction::write(0x52, vec![0x10, 0x02]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.set_alarm_interrupt(true), Ok(()));
+        assert_eq!(p19.set_alarm_interrupt(false), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn start_timer() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x24]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x20]),
+            I2cTransaction::write(0x52, vec![0x0A, 0x2C, 0x01]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x20]),
+            I2cTransaction::write(0x52, vec![0x0F, 0xA6]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x08]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0xA6]),
+            I2cTransaction::write(0x52, vec![0x0F, 0xA2]),
+            I2cTransaction::write(0x52, vec![0x0A, 0xFF, 0x0F]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0xA2]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x24]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.start_timer(TimerFrequency::Hz1, 300, true), Ok(()));
+        assert_eq!(p19.start_timer(TimerFrequency::Hz4096, 4095, false), Ok(()));
+        assert_eq!(
+            p19.start_timer(TimerFrequency::Hz64, 0, false),
+            Err(Error::ArgumentError)
+        );
+        assert_eq!(
+            p19.start_timer(TimerFrequency::Hz64, 4096, false),
+            Err(Error::ArgumentError)
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn timer_status() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0C], vec![0x2C, 0xF1]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x0C]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x04]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x10]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x04]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.timer_remaining(), Ok(300));
+        assert_eq!(p19.check_timer(), Ok(true));
+        assert_eq!(p19.set_timer_interrupt(true), Ok(()));
+        assert_eq!(p19.stop_timer(), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn update_interrupt() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x10]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x10]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x20]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x10]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.set_update_interval(UpdateInterval::Minute), Ok(()));
+        assert_eq!(p19.set_update_interval(UpdateInterval::Second), Ok(()));
+        assert_eq!(p19.set_update_interrupt(true), Ok(()));
+        assert_eq!(p19.check_update(), Ok(true));
+        assert_eq!(p19.check_update(), Ok(false));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_clock_output() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x35], vec![0xC0]),
+            I2cTransaction::write(0x52, vec![0x35, 0xC5]),
+            I2cTransaction::write_read(0x52, vec![0x35], vec![0xC5]),
+            I2cTransaction::write(0x52, vec![0x35, 0x40]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.set_clock_output(Some(ClockOutput::Hz1)), Ok(()));
+        assert_eq!(p19.set_clock_output(None), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn configure_event() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x13], vec![0x01]),
+            I2cTransaction::write(0x52, vec![0x13, 0x61]),
+            I2cTransaction::write_read(0x52, vec![0x13], vec![0x61]),
+            I2cTransaction::write(0x52, vec![0x13, 0x01]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x04]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x02]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(
+            p19.configure_event(EventEdge::Rising, EventFilter::Ms15_6),
+            Ok(())
+        );
+        assert_eq!(
+            p19.configure_event(EventEdge::Falling, EventFilter::None),
+            Ok(())
+        );
+        assert_eq!(p19.set_event_interrupt(true), Ok(()));
+        assert_eq!(p19.check_event(), Ok(true));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn enable_timestamp() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x80]),
+            I2cTransaction::write(0x52, vec![0x10, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x13], vec![0x41]),
+            I2cTransaction::write(0x52, vec![0x13, 0x46]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x80]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x80]),
+            I2cTransaction::write(0x52, vec![0x10, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x13], vec![0x42]),
+            I2cTransaction::write(0x52, vec![0x13, 0x45]),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x10, 0x80]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.enable_timestamp(TimestampSource::Event, true), Ok(()));
+        assert_eq!(
+            p19.enable_timestamp(TimestampSource::BackupSwitchover, false),
+            Ok(())
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn get_timestamp() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x80]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x14],
+                vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+            I2cTransaction::write_read(0x52, vec![0x10], vec![0x80]),
+            I2cTransaction::write_read(
+                0x52,
+                vec![0x14],
+                vec![0x03, 0x32, 0x48, 0x14, 0x08, 0x05, 0x23],
+            ),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.get_timestamp(), Ok(None));
+        assert_eq!(
+            p19.get_timestamp(),
+            Ok(Some(Timestamp {
+                count: 3,
+                date_time: DateTime::new(2023, 5, 8, 14, 48, 32).unwrap(),
+            }))
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn check_battery_switchover() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x21]),
+            I2cTransaction::write(0x52, vec![0x0E, 0x01]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x01]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.check_battery_switchover(), Ok(true));
+        assert_eq!(p19.check_battery_switchover(), Ok(false));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn update_eeprom() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x08]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x11]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x80]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x08]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.update_eeprom(), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn user_eeprom() {
+        let expectations = [
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x08]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x25, 0x29, 0xAB]),
+            I2cTransaction::write(0x52, vec![0x27, 0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x21]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x25, 0x2A, 0xCD]),
+            I2cTransaction::write(0x52, vec![0x27, 0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x21]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x08]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x08]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write(0x52, vec![0x25, 0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x00]),
+            I2cTransaction::write(0x52, vec![0x27, 0x22]),
+            I2cTransaction::write_read(0x52, vec![0x0E], vec![0x00]),
+            I2cTransaction::write_read(0x52, vec![0x26], vec![0x42]),
+            I2cTransaction::write_read(0x52, vec![0x0F], vec![0x08]),
+            I2cTransaction::write(0x52, vec![0x0F, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.write_user_eeprom(41, &[0xAB, 0xCD]), Ok(()));
+        let mut buffer = [0; 1];
+        assert_eq!(p19.read_user_eeprom(0, &mut buffer), Ok(()));
+        assert_eq!(buffer, [0x42]);
+        assert_eq!(
+            p19.write_user_eeprom(42, &[0xAB, 0xCD]),
+            Err(Error::ArgumentError)
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn user_ram() {
+        let expectations = [
+            I2cTransaction::write(0x52, vec![0x1F, 0x12, 0x34]),
+            I2cTransaction::write_read(0x52, vec![0x1F], vec![0x12, 0x34]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.write_user_ram([0x12, 0x34]), Ok(()));
+        assert_eq!(p19.read_user_ram(), Ok([0x12, 0x34]));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn offset() {
+        let expectations = [
+            I2cTransaction::write(0x52, vec![0x36, 0xFD]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x34]),
+            I2cTransaction::write(0x52, vec![0x37, 0xB4]),
+            I2cTransaction::write_read(0x52, vec![0x36], vec![0xFD]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0xB4]),
+            I2cTransaction::write(0x52, vec![0x36, 0x7F]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0xB4]),
+            I2cTransaction::write(0x52, vec![0x37, 0x34]),
+            I2cTransaction::write_read(0x52, vec![0x36], vec![0x7F]),
+            I2cTransaction::write_read(0x52, vec![0x37], vec![0x34]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p19 = P19 { i2c, address: 0x52 };
+
+        assert_eq!(p19.set_offset(-5), Ok(()));
+        assert_eq!(p19.get_offset(), Ok(-5));
+        assert_eq!(p19.set_offset(254), Ok(()));
+        assert_eq!(p19.get_offset(), Ok(254));
+        assert_eq!(p19.set_offset(256), Err(Error::ArgumentError));
+        i2c_clone.done();
+    }
 }
 
+pub mod alarm;
+pub mod datetime;
 pub mod whoami;
@@ -24,6 +24,18 @@ use rust_decimal::prelude::*;
 const REG_TEMP: u8 = 0xFA;
 const REG_PRESS: u8 = 0xF7;
 const REG_HUM: u8 = 0xFD;
+const REG_CTRL_HUM: u8 = 0xF2;
+const REG_STATUS: u8 = 0xF3;
+const REG_CTRL_MEAS: u8 = 0xF4;
+
+/// Humidity oversampling ×1.  Only takes effect after the next write to `ctrl_meas`.
+const CTRL_HUM_OVERSAMPLING: u8 = 0b001;
+/// Temperature oversampling ×16 and pressure oversampling ×2, in sleep mode.
+const CTRL_MEAS_SLEEP: u8 = 0b1010_1000;
+/// As [`CTRL_MEAS_SLEEP`], starting a single measurement.
+const CTRL_MEAS_FORCED: u8 = CTRL_MEAS_SLEEP | 0b01;
+const STATUS_MEASURING: u8 = 0x08;
+const MEASURE_POLLS: u16 = 10_000;
 
 const DIG_T: u8 = 0x88;
 const DIG_P: u8 = 0x8E;
@@ -33,6 +45,21 @@ pub type DigT = (u16, i16, i16);
 pub type DigP = (u16, i16, i16, i16, i16, i16, i16, i16, i16);
 pub type DigH = (u8, i16, u8, i16, i16, i8);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    I2cError(E),
+    /// A measurement did not finish in time.
+    Timeout,
+    /// A reading could not be represented in the requested type.
+    InvalidData,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Self::I2cError(error)
+    }
+}
+
 pub struct P2<I2C> {
     i2c: I2C,
     address: u8,
@@ -58,6 +85,10 @@ impl<I2C: I2c> Driver<I2C, I2C::Error> for P2<I2C> {
         self.load_temperature_data()?;
         self.load_pressure_data()?;
         self.load_humidity_data()?;
+        self.i2c
+            .write(self.address, &[REG_CTRL_HUM, CTRL_HUM_OVERSAMPLING])?;
+        self.i2c
+            .write(self.address, &[REG_CTRL_MEAS, CTRL_MEAS_SLEEP])?;
         Ok(self)
     }
 }
@@ -148,10 +179,26 @@ impl<I2C: I2c> P2<I2C> {
     }
 
     pub fn read_raw(&mut self) -> Result<(), I2C::Error> {
-        self.i2c.write(self.address, &[0xF4, 169])?;
+        self.i2c
+            .write(self.address, &[REG_CTRL_MEAS, CTRL_MEAS_FORCED])?;
         Ok(())
     }
 
+    /// Starts a single measurement and waits for it to finish, so that [`P2::celsius`],
+    /// [`P2::pascal`] and [`P2::relative`] return fresh readings.
+    pub fn measure(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.read_raw()?;
+        for _ in 0..MEASURE_POLLS {
+            let mut status: [u8; 1] = [0];
+            self.i2c
+                .write_read(self.address, &[REG_STATUS], &mut status)?;
+            if status[0] & STATUS_MEASURING == 0 {
+                return Ok(());
+            }
+        }
+        Err(Error::Timeout)
+    }
+
     fn compensate_t(&mut self, adc_t: i32) -> Decimal {
         let var_1: i32 = (((adc_t >> 3) - ((i32::from(self.temperature_data.unwrap().0)) << 1))
             * i32::from(self.temperature_data.unwrap().1))
@@ -295,6 +342,8 @@ mod test {
             ),
             I2cTransaction::write_read(0x77, vec![0xA1], vec![75]),
             I2cTransaction::write_read(0x77, vec![0xE1], vec![118, 1, 0, 18, 34, 3, 30]),
+            I2cTransaction::write(0x77, vec![0xF2, 0x01]),
+            I2cTransaction::write(0x77, vec![0xF4, 0xA8]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
@@ -415,6 +464,23 @@ mod test {
         i2c_clone.done();
     }
 
+    #[test]
+    pub fn measure() {
+        let expectations = [
+            I2cTransaction::write(0x77, vec![0xF4, 0xA9]),
+            I2cTransaction::write_read(0x77, vec![0xF3], vec![0x08]),
+            I2cTransaction::write_read(0x77, vec![0xF3], vec![0x09]),
+            I2cTransaction::write_read(0x77, vec![0xF3], vec![0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p2 = P2::new(i2c, 0x77).unwrap();
+        assert_eq!(p2.measure(), Ok(()));
+
+        i2c_clone.done();
+    }
+
     #[test]
     pub fn pascal() {
         let expectations = [I2cTransaction::write_read(
@@ -0,0 +1,231 @@
+//! Higher level events built by polling a [`P21`].
+//!
+//! Presses and releases are found by polling [`P21::is_pressed`], so presses shorter than the
+//! polling interval can be missed.  Double presses are detected by the button itself; see
+//! [`P21::set_double_press_duration`].
+
+use super::P21;
+use embedded_hal::i2c::I2c;
+use fugit::{ExtU32, MillisDurationU32, TimerInstantU32};
+
+pub type Instant = TimerInstantU32<1000>;
+
+/// A source of monotonic time in milliseconds.  Wrapping is handled as long as the button is
+/// polled at least once every 24 days.
+pub trait Monotonic {
+    fn now(&mut self) -> Instant;
+}
+
+impl<F: FnMut() -> Instant> Monotonic for F {
+    fn now(&mut self) -> Instant {
+        self()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Press,
+    Release,
+    DoublePress,
+    /// Follows the [`Event::Release`] of a press held for at least [`Timing::long_press`].
+    LongPress(MillisDurationU32),
+    /// Repeats while the button is held down.
+    Hold,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    /// Shortest press reported as [`Event::LongPress`].
+    pub long_press: MillisDurationU32,
+    /// Time held before the first [`Event::Hold`].
+    pub hold_delay: MillisDurationU32,
+    /// Time between subsequent [`Event::Hold`]s.
+    pub hold_repeat: MillisDurationU32,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            long_press: 1000.millis(),
+            hold_delay: 1000.millis(),
+            hold_repeat: 250.millis(),
+        }
+    }
+}
+
+/// A release can produce [`Event::Release`], [`Event::LongPress`] and [`Event::DoublePress`]
+/// from a single poll.
+const QUEUE_LENGTH: usize = 3;
+
+pub struct ButtonEvents<I2C, CLOCK> {
+    button: P21<I2C>,
+    clock: CLOCK,
+    timing: Timing,
+    pressed_at: Option<Instant>,
+    next_hold: Instant,
+    queue: [Option<Event>; QUEUE_LENGTH],
+}
+
+impl<I2C: I2c, CLOCK: Monotonic> ButtonEvents<I2C, CLOCK> {
+    pub const fn new(button: P21<I2C>, clock: CLOCK, timing: Timing) -> Self {
+        Self {
+            button,
+            clock,
+            timing,
+            pressed_at: None,
+            next_hold: Instant::from_ticks(0),
+            queue: [None; QUEUE_LENGTH],
+        }
+    }
+
+    pub const fn button(&mut self) -> &mut P21<I2C> {
+        &mut self.button
+    }
+
+    pub fn release(self) -> (P21<I2C>, CLOCK) {
+        (self.button, self.clock)
+    }
+
+    fn push(&mut self, event: Event) {
+        if let Some(slot) = self.queue.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(event);
+        }
+    }
+
+    const fn pop(&mut self) -> Option<Event> {
+        let event = self.queue[0].take();
+        self.queue.rotate_left(1);
+        event
+    }
+
+    fn check_double_press(&mut self) -> Result<(), I2C::Error> {
+        if self.button.was_double_pressed()? {
+            self.push(Event::DoublePress);
+        }
+        Ok(())
+    }
+
+    /// Returns the next event, if any.  Call this regularly, more often than
+    /// [`Timing::hold_repeat`].
+    pub fn poll(&mut self) -> Result<Option<Event>, I2C::Error> {
+        if let Some(event) = self.pop() {
+            return Ok(Some(event));
+        }
+        let now = self.clock.now();
+        match (self.button.is_pressed()?, self.pressed_at) {
+            (true, None) => {
+                self.pressed_at = Some(now);
+                self.next_hold = now + self.timing.hold_delay;
+                self.push(Event::Press);
+                self.check_double_press()?;
+            }
+            (true, Some(_)) if now >= self.next_hold => {
+                self.next_hold = now + self.timing.hold_repeat;
+                self.push(Event::Hold);
+            }
+            (false, Some(pressed_at)) => {
+                self.pressed_at = None;
+                self.push(Event::Release);
+                let held = now - pressed_at;
+                if held >= self.timing.long_press {
+                    self.push(Event::LongPress(held));
+                }
+                self.check_double_press()?;
+            }
+            _ => {}
+        }
+        Ok(self.pop())
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use core::cell::Cell;
+    use std::vec;
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use fugit::ExtU32;
+
+    use crate::p21::events::{ButtonEvents, Event, Instant, Timing};
+    use crate::p21::P21;
+
+    fn is_pressed(pressed: bool) -> I2cTransaction {
+        I2cTransaction::write_read(0x10, vec![0x11], vec![u8::from(!pressed)])
+    }
+
+    fn was_double_pressed(double: bool) -> I2cTransaction {
+        I2cTransaction::write_read(0x10, vec![0x13], vec![u8::from(double)])
+    }
+
+    #[test]
+    pub fn press_hold_release() {
+        let expectations = [
+            is_pressed(true),
+            was_double_pressed(false),
+            is_pressed(true),
+            is_pressed(true),
+            is_pressed(true),
+            is_pressed(false),
+            was_double_pressed(false),
+            is_pressed(false),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let time = Cell::new(0);
+        let timing = Timing {
+            long_press: 800.millis(),
+            hold_delay: 500.millis(),
+            hold_repeat: 200.millis(),
+        };
+        let mut events = ButtonEvents::new(
+            P21 { i2c, address: 0x10 },
+            || Instant::from_ticks(time.get()),
+            timing,
+        );
+
+        let mut poll_at = |ticks| {
+            time.set(ticks);
+            events.poll().unwrap()
+        };
+        assert_eq!(poll_at(0), Some(Event::Press));
+        assert_eq!(poll_at(400), None);
+        assert_eq!(poll_at(500), Some(Event::Hold));
+        assert_eq!(poll_at(600), None);
+        assert_eq!(poll_at(900), Some(Event::Release));
+        assert_eq!(poll_at(900), Some(Event::LongPress(900.millis())));
+        assert_eq!(poll_at(1000), None);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn double_press() {
+        let expectations = [
+            is_pressed(true),
+            was_double_pressed(false),
+            is_pressed(false),
+            was_double_pressed(false),
+            is_pressed(true),
+            was_double_pressed(true),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let time = Cell::new(u32::MAX - 100);
+        let mut events = ButtonEvents::new(
+            P21 { i2c, address: 0x10 },
+            || Instant::from_ticks(time.get()),
+            Timing::default(),
+        );
+
+        assert_eq!(events.poll(), Ok(Some(Event::Press)));
+        time.set(50);
+        assert_eq!(events.poll(), Ok(Some(Event::Release)));
+        assert_eq!(events.poll(), Ok(Some(Event::Press)));
+        assert_eq!(events.poll(), Ok(Some(Event::DoublePress)));
+        i2c_clone.done();
+    }
+}
@@ -310,4 +310,5 @@ mod test {
 }
 
 pub mod atmel;
+pub mod events;
 pub mod whoami;
@@ -110,4 +110,5 @@ mod test {
 }
 
 pub mod atmel;
+pub mod scaled;
 pub mod whoami;
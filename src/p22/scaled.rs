@@ -0,0 +1,157 @@
+//! Maps raw [`P22`] readings onto a user range, with a dead band to stop the value jittering
+//! and optional detents.
+
+use super::P22;
+use embedded_hal::i2c::I2c;
+
+/// The largest raw reading, at the end of travel.
+pub const RAW_MAX: u16 = 1023;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scaled {
+    minimum: f32,
+    maximum: f32,
+    dead_band: u16,
+    detents: Option<u16>,
+    stable: Option<u16>,
+    reported: Option<f32>,
+}
+
+impl Scaled {
+    /// Values run from `minimum` to `maximum`, which may be reversed.
+    #[must_use]
+    pub const fn new(minimum: f32, maximum: f32) -> Self {
+        Self {
+            minimum,
+            maximum,
+            dead_band: 0,
+            detents: None,
+            stable: None,
+            reported: None,
+        }
+    }
+
+    /// Ignores raw readings within `counts` of the last accepted reading.
+    #[must_use]
+    pub const fn with_dead_band(mut self, counts: u16) -> Self {
+        self.dead_band = counts;
+        self
+    }
+
+    /// Snaps values to `positions` evenly spaced points, including both ends of the range.  Fewer
+    /// than two positions disables detents.
+    #[must_use]
+    pub const fn with_detents(mut self, positions: u16) -> Self {
+        self.detents = if positions < 2 { None } else { Some(positions) };
+        self
+    }
+
+    fn value(&self, raw: u16) -> f32 {
+        let fraction = self.detents.map_or_else(
+            || f32::from(raw) / f32::from(RAW_MAX),
+            |positions| {
+                let steps = u32::from(positions - 1);
+                let step = (u32::from(raw) * steps + u32::from(RAW_MAX / 2)) / u32::from(RAW_MAX);
+                u16::try_from(step).map_or(1.0, f32::from) / f32::from(positions - 1)
+            },
+        );
+        libm::fmaf(self.maximum - self.minimum, fraction, self.minimum)
+    }
+
+    /// Scales a raw reading, holding the previous value while the reading stays within the dead
+    /// band.
+    pub fn update(&mut self, raw: u16) -> f32 {
+        let clamped = raw.min(RAW_MAX);
+        let stable = match self.stable {
+            Some(stable) if stable.abs_diff(clamped) <= self.dead_band => stable,
+            _ => clamped,
+        };
+        self.stable = Some(stable);
+        self.value(stable)
+    }
+
+    pub fn read<I2C: I2c>(&mut self, p22: &mut P22<I2C>) -> Result<f32, I2C::Error> {
+        Ok(self.update(p22.read()?))
+    }
+
+    /// Reads the value, returning it only if it has moved by at least `threshold` since it was
+    /// last returned.  The first reading is always returned.
+    pub fn read_change<I2C: I2c>(
+        &mut self,
+        p22: &mut P22<I2C>,
+        threshold: f32,
+    ) -> Result<Option<f32>, I2C::Error> {
+        let value = self.read(p22)?;
+        if self
+            .reported
+            .is_some_and(|reported| (value - reported).abs() < threshold)
+        {
+            return Ok(None);
+        }
+        self.reported = Some(value);
+        Ok(Some(value))
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::vec;
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    use crate::p22::scaled::Scaled;
+    use crate::p22::P22;
+
+    #[test]
+    pub fn range() {
+        let mut scaled = Scaled::new(0.0, 100.0);
+        assert!((scaled.update(0) - 0.0).abs() < 1e-4);
+        assert!((scaled.update(1023) - 100.0).abs() < 1e-4);
+        assert!((scaled.update(2000) - 100.0).abs() < 1e-4);
+
+        let mut reversed = Scaled::new(10.0, -10.0);
+        assert!((reversed.update(0) - 10.0).abs() < 1e-4);
+        assert!((reversed.update(1023) + 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    pub fn dead_band() {
+        let mut scaled = Scaled::new(0.0, 1023.0).with_dead_band(4);
+        assert!((scaled.update(500) - 500.0).abs() < 1e-3);
+        assert!((scaled.update(504) - 500.0).abs() < 1e-3);
+        assert!((scaled.update(496) - 500.0).abs() < 1e-3);
+        assert!((scaled.update(505) - 505.0).abs() < 1e-3);
+        assert!((scaled.update(502) - 505.0).abs() < 1e-3);
+    }
+
+    #[test]
+    pub fn detents() {
+        let mut scaled = Scaled::new(0.0, 4.0).with_detents(5);
+        assert!((scaled.update(0) - 0.0).abs() < 1e-4);
+        assert!((scaled.update(127) - 0.0).abs() < 1e-4);
+        assert!((scaled.update(128) - 1.0).abs() < 1e-4);
+        assert!((scaled.update(511) - 2.0).abs() < 1e-4);
+        assert!((scaled.update(1023) - 4.0).abs() < 1e-4);
+        assert_eq!(Scaled::new(0.0, 1.0).with_detents(1), Scaled::new(0.0, 1.0));
+    }
+
+    #[test]
+    pub fn read_change() {
+        let reading =
+            |raw: u16| I2cTransaction::write_read(0x35, vec![0x05], raw.to_be_bytes().to_vec());
+        let expectations = [reading(0), reading(20), reading(60), reading(1023)];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p22 = P22 { i2c, address: 0x35 };
+        let mut scaled = Scaled::new(0.0, 1023.0);
+
+        assert_eq!(scaled.read_change(&mut p22, 50.0), Ok(Some(0.0)));
+        assert_eq!(scaled.read_change(&mut p22, 50.0), Ok(None));
+        assert_eq!(scaled.read_change(&mut p22, 50.0), Ok(Some(60.0)));
+        assert_eq!(scaled.read_change(&mut p22, 50.0), Ok(Some(1023.0)));
+        i2c_clone.done();
+    }
+}
@@ -0,0 +1,186 @@
+//! Keeps the [`P23`] temperature and humidity compensation up to date from another sensor.
+
+use super::{AirQuality, Error, P23};
+use crate::p2::{self, P2};
+use embedded_hal::i2c::I2c;
+use rust_decimal::prelude::ToPrimitive;
+
+/// A source of ambient temperature and humidity.
+pub trait Environment {
+    type Error;
+
+    /// Temperature in degrees Celsius and relative humidity in percent, from the same
+    /// measurement.
+    fn read(&mut self) -> Result<(f32, f32), Self::Error>;
+}
+
+/// Each reading starts a new measurement and waits for it to finish.
+impl<I2C: I2c> Environment for P2<I2C> {
+    type Error = p2::Error<I2C::Error>;
+
+    fn read(&mut self) -> Result<(f32, f32), Self::Error> {
+        self.measure()?;
+        // Reading the temperature first updates the humidity compensation.
+        let celsius = self.celsius()?.to_f32().ok_or(p2::Error::InvalidData)?;
+        Ok((celsius, self.relative()?.to_num()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompensationError<E, S> {
+    AirQuality(Error<E>),
+    Environment(S),
+}
+
+impl<E, S> From<Error<E>> for CompensationError<E, S> {
+    fn from(error: Error<E>) -> Self {
+        Self::AirQuality(error)
+    }
+}
+
+/// A [`P23`] compensated by an [`Environment`], such as a [`P2`].
+pub struct Compensated<I2C, ENV> {
+    p23: P23<I2C>,
+    environment: ENV,
+}
+
+impl<I2C: I2c, ENV: Environment> Compensated<I2C, ENV> {
+    pub const fn new(p23: P23<I2C>, environment: ENV) -> Self {
+        Self { p23, environment }
+    }
+
+    pub const fn p23(&mut self) -> &mut P23<I2C> {
+        &mut self.p23
+    }
+
+    pub const fn environment(&mut self) -> &mut ENV {
+        &mut self.environment
+    }
+
+    pub fn release(self) -> (P23<I2C>, ENV) {
+        (self.p23, self.environment)
+    }
+
+    /// Reads the environment and passes it on to the [`P23`].
+    pub fn compensate(&mut self) -> Result<(), CompensationError<I2C::Error, ENV::Error>> {
+        let (celsius, relative_humidity) = self
+            .environment
+            .read()
+            .map_err(CompensationError::Environment)?;
+        self.p23.set_environment(celsius, relative_humidity)?;
+        Ok(())
+    }
+
+    /// Refreshes the compensation, then reads the air quality.
+    pub fn read(&mut self) -> Result<AirQuality, CompensationError<I2C::Error, ENV::Error>> {
+        self.compensate()?;
+        Ok(self.p23.read()?)
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
+mod test {
+    extern crate std;
+    use std::vec;
+    extern crate embedded_hal;
+    extern crate embedded_hal_mock;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    use crate::p2::P2;
+    use crate::p23::compensation::{Compensated, CompensationError, Environment};
+    use crate::p23::{AirQuality, Error, P23};
+    use crate::Driver;
+
+    struct Fixed {
+        celsius: f32,
+        relative_humidity: Result<f32, ()>,
+    }
+
+    impl Environment for Fixed {
+        type Error = ();
+
+        fn read(&mut self) -> Result<(f32, f32), ()> {
+            Ok((self.celsius, self.relative_humidity?))
+        }
+    }
+
+    #[test]
+    pub fn read() {
+        let expectations = [
+            I2cTransaction::write(0x53, vec![0x13, 0x2A, 0x48]),
+            I2cTransaction::write(0x53, vec![0x15, 0x00, 0x32]),
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x82, 0x02, 0x20, 0x00, 0x58, 0x02]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut compensated = Compensated::new(
+            P23 { i2c, address: 0x53 },
+            Fixed {
+                celsius: 15.5,
+                relative_humidity: Ok(25.0),
+            },
+        );
+
+        assert_eq!(
+            compensated.read(),
+            Ok(AirQuality {
+                aqi: 2,
+                tvoc: 32,
+                eco2: 600
+            })
+        );
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn p2() {
+        let expectations = [
+            I2cTransaction::write(0x77, vec![0xF4, 0xA9]),
+            I2cTransaction::write_read(0x77, vec![0xF3], vec![0x08]),
+            I2cTransaction::write_read(0x77, vec![0xF3], vec![0x00]),
+            I2cTransaction::write_read(0x77, vec![0xFA], vec![129, 145, 0]),
+            I2cTransaction::write_read(0x77, vec![0xFD], vec![128, 0]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p2 = P2::new(i2c, 0x77).unwrap();
+        p2.temperature_data = Some((28834, 26639, 50));
+        p2.humidity_data = Some((75, 374, 0, 290, 50, 30));
+
+        let (celsius, relative_humidity) = p2.read().unwrap();
+        assert!((celsius - 22.03).abs() < 1e-4);
+        assert!((relative_humidity - 80.792).abs() < 1e-3);
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn errors() {
+        let expectations = [];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut compensated = Compensated::new(
+            P23 { i2c, address: 0x53 },
+            Fixed {
+                celsius: 15.5,
+                relative_humidity: Err(()),
+            },
+        );
+
+        assert_eq!(
+            compensated.compensate(),
+            Err(CompensationError::Environment(()))
+        );
+        compensated.environment().relative_humidity = Ok(120.0);
+        assert_eq!(
+            compensated.compensate(),
+            Err(CompensationError::AirQuality(Error::ArgumentError))
+        );
+
+        i2c_clone.done();
+    }
+}
@@ -15,20 +15,39 @@
 use crate::Driver;
 use embedded_hal::i2c::I2c;
 use fixed::types::{U10F6, U7F9};
+use num_enum::IntoPrimitive;
 
 const REG_OPMODE: u8 = 0x10;
 const REG_CONFIG: u8 = 0x11;
 const REG_TEMP_IN: u8 = 0x13;
 const REG_RH_IN: u8 = 0x15;
 const REG_DEVICE_STATUS: u8 = 0x20;
+const REG_GPR_READ: u8 = 0x48;
 
 const VAL_OPMODE_STANDARD: u8 = 0x02;
 
+const STATUS_RUNNING: u8 = 0b1000_0000;
+const STATUS_ERROR: u8 = 0b0100_0000;
+const STATUS_VALIDITY: u8 = 0b0000_1100;
+const STATUS_NEW_DATA: u8 = 0b0000_0010;
+const CONFIG_ACTIVE_HIGH: u8 = 0b0100_0000;
+const CONFIG_PUSH_PULL: u8 = 0b0010_0000;
+const CONFIG_NEW_GPR: u8 = 0b0000_1000;
+const CONFIG_NEW_DATA: u8 = 0b0000_0010;
+const CONFIG_INTERRUPT_ENABLE: u8 = 0b0000_0001;
+const ZERO_CELSIUS: f32 = 273.15;
+/// Raw resistances are stored as `2048 * log2(ohms)`.
+const RESISTANCE_SCALE: f32 = 2048.0;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Error<E> {
     I2cError(E),
     ArgumentError,
     UnexpectedDevice,
+    /// The sensor has not finished warming up since it was last powered or woken.
+    WarmingUp,
+    /// The sensor reported that its output is not valid.
+    InvalidOutput,
 }
 
 impl<E> From<E> for Error<E> {
@@ -37,6 +56,96 @@ impl<E> From<E> for Error<E> {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum OperatingMode {
+    DeepSleep = 0x00,
+    Idle = 0x01,
+    Standard = 0x02,
+    Reset = 0xF0,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Validity {
+    Normal,
+    /// About three minutes after power on or leaving deep sleep.
+    WarmUp,
+    /// The first hour of operation of a new sensor.  Readings are given but are less accurate.
+    InitialStartUp,
+    InvalidOutput,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// An operating mode is running.
+    pub running: bool,
+    /// The last command or operating mode was invalid.
+    pub error: bool,
+    pub validity: Validity,
+    /// New readings are available.
+    pub new_data: bool,
+}
+
+impl From<u8> for Status {
+    fn from(status: u8) -> Self {
+        Self {
+            running: status & STATUS_RUNNING != 0,
+            error: status & STATUS_ERROR != 0,
+            validity: match (status & STATUS_VALIDITY) >> 2_u8 {
+                0 => Validity::Normal,
+                1 => Validity::WarmUp,
+                2 => Validity::InitialStartUp,
+                _ => Validity::InvalidOutput,
+            },
+            new_data: status & STATUS_NEW_DATA != 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Polarity {
+    ActiveLow,
+    ActiveHigh,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Drive {
+    OpenDrain,
+    PushPull,
+}
+
+/// Configuration of the INT pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interrupt {
+    pub polarity: Polarity,
+    pub drive: Drive,
+    /// Signal when new air quality readings are available.
+    pub new_data: bool,
+    /// Signal when new raw resistances are available.
+    pub new_raw: bool,
+}
+
+impl From<Interrupt> for u8 {
+    fn from(interrupt: Interrupt) -> Self {
+        let flag = |set: bool, bit: Self| if set { bit } else { 0 };
+        CONFIG_INTERRUPT_ENABLE
+            | flag(
+                interrupt.polarity == Polarity::ActiveHigh,
+                CONFIG_ACTIVE_HIGH,
+            )
+            | flag(interrupt.drive == Drive::PushPull, CONFIG_PUSH_PULL)
+            | flag(interrupt.new_data, CONFIG_NEW_DATA)
+            | flag(interrupt.new_raw, CONFIG_NEW_GPR)
+    }
+}
+
+/// Hotplate resistances in ohms, as measured in [`OperatingMode::Standard`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Resistances {
+    pub hotplate_0: f32,
+    pub hotplate_2: f32,
+}
+
 #[derive(PartialEq, Debug, Eq)]
 pub struct AirQuality {
     pub aqi: u8,
@@ -82,10 +191,35 @@ impl<I2C: I2c> P23<I2C> {
         Ok(())
     }
 
-    pub fn read(&mut self) -> Result<AirQuality, I2C::Error> {
+    /// Sets the temperature in degrees Celsius and relative humidity in percent used to
+    /// compensate readings.
+    pub fn set_environment(
+        &mut self,
+        celsius: f32,
+        relative_humidity: f32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let temperature =
+            U10F6::checked_from_num(celsius + ZERO_CELSIUS).ok_or(Error::ArgumentError)?;
+        if !(0.0..=100.0).contains(&relative_humidity) {
+            return Err(Error::ArgumentError);
+        }
+        let humidity = U7F9::checked_from_num(relative_humidity).ok_or(Error::ArgumentError)?;
+        self.set_temperature(temperature)?;
+        self.set_humidity(humidity)?;
+        Ok(())
+    }
+
+    /// Fails with [`Error::WarmingUp`] or [`Error::InvalidOutput`] rather than returning
+    /// unreliable readings.  Readings during [`Validity::InitialStartUp`] are returned.
+    pub fn read(&mut self) -> Result<AirQuality, Error<I2C::Error>> {
         let mut data: [u8; 6] = [0; 6];
         self.i2c
             .write_read(self.address, &[REG_DEVICE_STATUS], &mut data)?;
+        match Status::from(data[0]).validity {
+            Validity::WarmUp => return Err(Error::WarmingUp),
+            Validity::InvalidOutput => return Err(Error::InvalidOutput),
+            Validity::Normal | Validity::InitialStartUp => {}
+        }
         Ok(AirQuality {
             aqi: data[1],
             tvoc: i16::from_le_bytes([data[2], data[3]]),
@@ -93,6 +227,37 @@ impl<I2C: I2c> P23<I2C> {
         })
     }
 
+    pub fn read_status(&mut self) -> Result<Status, I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[REG_DEVICE_STATUS], &mut data)?;
+        Ok(Status::from(data[0]))
+    }
+
+    /// Leaving [`OperatingMode::DeepSleep`] restarts the warm up.  After
+    /// [`OperatingMode::Reset`] the sensor must be put in another mode before use.
+    pub fn set_mode(&mut self, mode: OperatingMode) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[REG_OPMODE, mode.into()])
+    }
+
+    /// Configures the INT pin, or disables it with `None`.
+    pub fn set_interrupt(&mut self, interrupt: Option<Interrupt>) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[REG_CONFIG, interrupt.map_or(0, u8::from)])
+    }
+
+    pub fn read_resistances(&mut self) -> Result<Resistances, I2C::Error> {
+        let mut data: [u8; 8] = [0; 8];
+        self.i2c
+            .write_read(self.address, &[REG_GPR_READ], &mut data)?;
+        let ohms =
+            |low, high| libm::exp2f(f32::from(u16::from_le_bytes([low, high])) / RESISTANCE_SCALE);
+        Ok(Resistances {
+            hotplate_0: ohms(data[0], data[1]),
+            hotplate_2: ohms(data[4], data[5]),
+        })
+    }
+
     pub fn data_ready(&mut self) -> Result<bool, I2C::Error> {
         let mut data: [u8; 6] = [0; 6];
         self.i2c
@@ -115,7 +280,9 @@ mod test {
     extern crate embedded_hal_mock;
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p23::{AirQuality, P23};
+    use crate::p23::{
+        AirQuality, Drive, Error, Interrupt, OperatingMode, Polarity, Status, Validity, P23,
+    };
 
     #[test]
     pub fn new() {
@@ -160,6 +327,28 @@ mod test {
         i2c_clone.done();
     }
 
+    #[test]
+    pub fn set_environment() {
+        let expectations = [
+            I2cTransaction::write(0x53, vec![0x13, 0x8A, 0x4A]),
+            I2cTransaction::write(0x53, vec![0x15, 0x00, 0x64]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p23 = P23 { i2c, address: 0x53 };
+
+        assert_eq!(p23.set_environment(25.0, 50.0), Ok(()));
+        assert_eq!(p23.set_environment(-300.0, 50.0), Err(Error::ArgumentError));
+        assert_eq!(p23.set_environment(25.0, 101.0), Err(Error::ArgumentError));
+        assert_eq!(
+            p23.set_environment(25.0, f32::NAN),
+            Err(Error::ArgumentError)
+        );
+
+        i2c_clone.done();
+    }
+
     #[test]
     pub fn read() {
         let expectations = [I2cTransaction::write_read(
@@ -215,6 +404,126 @@ mod test {
 
         i2c_clone.done();
     }
+
+    #[test]
+    pub fn read_not_ready() {
+        let expectations = [
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x86, 0x01, 0x00, 0x00, 0x90, 0x01]),
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x8E, 0x01, 0x00, 0x00, 0x90, 0x01]),
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x8A, 0x01, 0x05, 0x00, 0x90, 0x01]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p23 = P23 { i2c, address: 0x53 };
+
+        assert_eq!(p23.read(), Err(Error::WarmingUp));
+        assert_eq!(p23.read(), Err(Error::InvalidOutput));
+        assert_eq!(
+            p23.read(),
+            Ok(AirQuality {
+                aqi: 1,
+                tvoc: 5,
+                eco2: 400
+            })
+        );
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_status() {
+        let expectations = [
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x86]),
+            I2cTransaction::write_read(0x53, vec![0x20], vec![0x40]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p23 = P23 { i2c, address: 0x53 };
+
+        assert_eq!(
+            p23.read_status(),
+            Ok(Status {
+                running: true,
+                error: false,
+                validity: Validity::WarmUp,
+                new_data: true,
+            })
+        );
+        assert_eq!(
+            p23.read_status(),
+            Ok(Status {
+                running: false,
+                error: true,
+                validity: Validity::Normal,
+                new_data: false,
+            })
+        );
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn modes_and_interrupts() {
+        let expectations = [
+            I2cTransaction::write(0x53, vec![0x10, 0x00]),
+            I2cTransaction::write(0x53, vec![0x10, 0xF0]),
+            I2cTransaction::write(0x53, vec![0x10, 0x01]),
+            I2cTransaction::write(0x53, vec![0x11, 0x63]),
+            I2cTransaction::write(0x53, vec![0x11, 0x09]),
+            I2cTransaction::write(0x53, vec![0x11, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p23 = P23 { i2c, address: 0x53 };
+
+        assert_eq!(p23.set_mode(OperatingMode::DeepSleep), Ok(()));
+        assert_eq!(p23.set_mode(OperatingMode::Reset), Ok(()));
+        assert_eq!(p23.set_mode(OperatingMode::Idle), Ok(()));
+        assert_eq!(
+            p23.set_interrupt(Some(Interrupt {
+                polarity: Polarity::ActiveHigh,
+                drive: Drive::PushPull,
+                new_data: true,
+                new_raw: false,
+            })),
+            Ok(())
+        );
+        assert_eq!(
+            p23.set_interrupt(Some(Interrupt {
+                polarity: Polarity::ActiveLow,
+                drive: Drive::OpenDrain,
+                new_data: false,
+                new_raw: true,
+            })),
+            Ok(())
+        );
+        assert_eq!(p23.set_interrupt(None), Ok(()));
+
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_resistances() {
+        let expectations = [I2cTransaction::write_read(
+            0x53,
+            vec![0x48],
+            vec![0x00, 0x80, 0x00, 0x00, 0x00, 0xA0, 0x00, 0x00],
+        )];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p23 = P23 { i2c, address: 0x53 };
+
+        let resistances = p23.read_resistances().unwrap();
+        assert!((resistances.hotplate_0 - 65536.0).abs() < 1.0);
+        assert!((resistances.hotplate_2 - 1_048_576.0).abs() < 16.0);
+
+        i2c_clone.done();
+    }
 }
 
+pub mod compensation;
 pub mod whoami;
@@ -16,23 +16,39 @@
 
 use crate::Driver;
 use embedded_hal::i2c::I2c;
-use fixed::types::I2F14;
 use num_enum::IntoPrimitive;
 
 const REG_CONTROL1: u8 = 0x20;
 const REG_CONTROL3: u8 = 0x22;
 const REG_CONTROL4: u8 = 0x23;
-const REG_CONTROL5: u8 = 0x25;
+const REG_CONTROL5: u8 = 0x24;
 const REG_STATUS: u8 = 0x27;
 const OUT_X_L: u8 = 0x28;
+const FIFO_CTRL: u8 = 0x2E;
+const FIFO_SRC: u8 = 0x2F;
 const INT1_SRC: u8 = 0x31;
 const CLICK_CFG: u8 = 0x38;
 const CLICK_SRC: u8 = 0x39;
 const CLICK_THS: u8 = 0x3A;
 
+const CONTROL1_RATE: u8 = 0b1111_0000;
+const CONTROL1_LOW_POWER: u8 = 0b0000_1000;
+const CONTROL4_RANGE: u8 = 0b0011_0000;
+const CONTROL4_HIGH_RESOLUTION: u8 = 0b0000_1000;
+const CONTROL5_FIFO_ENABLE: u8 = 0b0100_0000;
+const CONTROL5_LATCH_INT1: u8 = 0b0000_1000;
+const FIFO_WATERMARK: u8 = 0b1000_0000;
+const FIFO_OVERRUN: u8 = 0b0100_0000;
+const FIFO_STORED: u8 = 0b0001_1111;
+/// Samples held by the FIFO.
+pub const FIFO_SIZE: usize = 32;
+const STANDARD_GRAVITY: f32 = 9.806_65;
+
 pub struct P26<I2C> {
     i2c: I2C,
     address: u8,
+    range: Gravity,
+    resolution: Resolution,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -55,23 +71,126 @@ impl<E> From<E> for Error<E> {
     }
 }
 
-#[derive(IntoPrimitive)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
 #[repr(u8)]
 pub enum Gravity {
     EarthTimes2 = 0b0000_0000,
+    EarthTimes4 = 0b0001_0000,
+    EarthTimes8 = 0b0010_0000,
+    EarthTimes16 = 0b0011_0000,
+}
+
+/// Bits per sample, trading noise against power.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// 8 bits.
+    LowPower,
+    /// 10 bits.
+    Normal,
+    /// 12 bits.
+    HighResolution,
+}
+
+impl Resolution {
+    /// Samples are left aligned in 16 bits.
+    const fn shift(self) -> u8 {
+        match self {
+            Self::LowPower => 8,
+            Self::Normal => 6,
+            Self::HighResolution => 4,
+        }
+    }
+}
+
+/// Output data rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum Rate {
+    PowerDown = 0x00,
+    Hz1 = 0x10,
+    Hz10 = 0x20,
+    Hz25 = 0x30,
+    Hz50 = 0x40,
+    Hz100 = 0x50,
+    Hz200 = 0x60,
+    Hz400 = 0x70,
+    /// Only available in [`Resolution::LowPower`].
+    Hz1600 = 0x80,
+    /// 5376 Hz in [`Resolution::LowPower`].
+    Hz1344 = 0x90,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(u8)]
+pub enum FifoMode {
+    /// The FIFO is disabled and only the latest sample is kept.
+    Bypass = 0x00,
+    /// Collects samples until full, then stops.
+    Fifo = 0x40,
+    /// Collects samples, discarding the oldest when full.
+    Stream = 0x80,
+    /// Streams until an interrupt on INT1 fires, then behaves as [`FifoMode::Fifo`].
+    StreamToFifo = 0xC0,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FifoStatus {
+    /// At least the watermark number of samples are stored.
+    pub watermark: bool,
+    /// The FIFO is full and, in stream mode, samples are being lost.
+    pub overrun: bool,
+    /// Unread samples, up to [`FIFO_SIZE`].
+    pub samples: u8,
+}
+
+impl From<u8> for FifoStatus {
+    fn from(status: u8) -> Self {
+        let overrun = status & FIFO_OVERRUN != 0;
+        Self {
+            watermark: status & FIFO_WATERMARK != 0,
+            overrun,
+            // The count stops at 31 and the overrun flag marks the 32nd sample.
+            samples: if overrun {
+                FIFO_STORED + 1
+            } else {
+                status & FIFO_STORED
+            },
+        }
+    }
+}
+
+/// Milli-g per digit, from the datasheet.
+const fn sensitivity(range: Gravity, resolution: Resolution) -> f32 {
+    let high_resolution = match range {
+        Gravity::EarthTimes2 => 1.0,
+        Gravity::EarthTimes4 => 2.0,
+        Gravity::EarthTimes8 => 4.0,
+        Gravity::EarthTimes16 => 12.0,
+    };
+    match resolution {
+        Resolution::LowPower => high_resolution * 16.0,
+        Resolution::Normal => high_resolution * 4.0,
+        Resolution::HighResolution => high_resolution,
+    }
 }
 
 impl<I2C: I2c> Driver<I2C, Error<I2C::Error>> for P26<I2C> {
     fn new_inner(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            range: Gravity::EarthTimes2,
+            resolution: Resolution::Normal,
+        }
     }
 
     fn init_inner(mut self) -> Result<Self, Error<I2C::Error>> {
         self.i2c.write(self.address, &[REG_CONTROL1, 0x07])?;
         self.i2c.write(self.address, &[REG_CONTROL4, 0x88])?;
+        self.resolution = Resolution::HighResolution;
 
         self.set_range(Gravity::EarthTimes2)?;
-        self.set_rate(400)?;
+        self.set_rate(Rate::Hz400)?;
         Ok(self)
     }
 }
@@ -97,41 +216,142 @@ impl<I2C: I2c> P26<I2C> {
             self.address,
             &[
                 REG_CONTROL4,
-                (data[0] & 0b1100_1111) | <Gravity as core::convert::Into<u8>>::into(range),
+                (data[0] & !CONTROL4_RANGE) | <Gravity as core::convert::Into<u8>>::into(range),
             ],
         )?;
+        self.range = range;
         Ok(())
     }
 
-    pub fn set_rate(&mut self, rate: u16) -> Result<(), Error<I2C::Error>> {
-        let rr = match rate {
-            0 => 0x00,
-            1 => 0x10,
-            10 => 0x20,
-            25 => 0x30,
-            50 => 0x40,
-            100 => 0x50,
-            200 => 0x60,
-            400 => 0x70,
-            _ => return Err(Error::ArgumentError),
-        };
+    pub fn set_resolution(&mut self, resolution: Resolution) -> Result<(), I2C::Error> {
         let mut data: [u8; 1] = [0];
         self.i2c
             .write_read(self.address, &[0x80 | REG_CONTROL1], &mut data)?;
+        let low_power = if resolution == Resolution::LowPower {
+            CONTROL1_LOW_POWER
+        } else {
+            0
+        };
+        self.i2c.write(
+            self.address,
+            &[REG_CONTROL1, (data[0] & !CONTROL1_LOW_POWER) | low_power],
+        )?;
         self.i2c
-            .write(self.address, &[REG_CONTROL1, (data[0] & 0x0f) | rr])?;
+            .write_read(self.address, &[0x80 | REG_CONTROL4], &mut data)?;
+        let high_resolution = if resolution == Resolution::HighResolution {
+            CONTROL4_HIGH_RESOLUTION
+        } else {
+            0
+        };
+        self.i2c.write(
+            self.address,
+            &[
+                REG_CONTROL4,
+                (data[0] & !CONTROL4_HIGH_RESOLUTION) | high_resolution,
+            ],
+        )?;
+        self.resolution = resolution;
         Ok(())
     }
 
-    pub fn acceleration(&mut self) -> Result<(I2F14, I2F14, I2F14), I2C::Error> {
+    pub fn set_rate(&mut self, rate: Rate) -> Result<(), I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[0x80 | REG_CONTROL1], &mut data)?;
+        self.i2c.write(
+            self.address,
+            &[
+                REG_CONTROL1,
+                (data[0] & !CONTROL1_RATE) | <Rate as core::convert::Into<u8>>::into(rate),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Converts a left aligned sample to g for the current range and resolution.
+    fn scale(&self, low: u8, high: u8) -> f32 {
+        let counts = i16::from_le_bytes([low, high]) >> self.resolution.shift();
+        f32::from(counts) * sensitivity(self.range, self.resolution) / 1000.0
+    }
+
+    /// Acceleration along each axis in g.
+    pub fn acceleration(&mut self) -> Result<(f32, f32, f32), I2C::Error> {
         let mut data: [u8; 6] = [0; 6];
         self.i2c
             .write_read(self.address, &[0x80 | OUT_X_L], &mut data)?;
 
         Ok((
-            I2F14::from_bits(i16::from_le_bytes([data[0], data[1]])),
-            I2F14::from_bits(i16::from_le_bytes([data[2], data[3]])),
-            I2F14::from_bits(i16::from_le_bytes([data[4], data[5]])),
+            self.scale(data[0], data[1]),
+            self.scale(data[2], data[3]),
+            self.scale(data[4], data[5]),
+        ))
+    }
+
+    /// Empties the FIFO and switches to `mode`.  The watermark flag is raised once more than
+    /// `watermark` samples, from 0 to 31, are stored.
+    pub fn set_fifo(&mut self, mode: FifoMode, watermark: u8) -> Result<(), Error<I2C::Error>> {
+        if watermark > FIFO_STORED {
+            return Err(Error::ArgumentError);
+        }
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[0x80 | REG_CONTROL5], &mut data)?;
+        let enable = if mode == FifoMode::Bypass {
+            0
+        } else {
+            CONTROL5_FIFO_ENABLE
+        };
+        self.i2c.write(
+            self.address,
+            &[REG_CONTROL5, (data[0] & !CONTROL5_FIFO_ENABLE) | enable],
+        )?;
+        // Passing through bypass mode clears the FIFO.
+        self.i2c
+            .write(self.address, &[FIFO_CTRL, FifoMode::Bypass.into()])?;
+        self.i2c.write(
+            self.address,
+            &[
+                FIFO_CTRL,
+                <FifoMode as core::convert::Into<u8>>::into(mode) | watermark,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn fifo_status(&mut self) -> Result<FifoStatus, I2C::Error> {
+        let mut data: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[0x80 | FIFO_SRC], &mut data)?;
+        Ok(FifoStatus::from(data[0]))
+    }
+
+    /// Drains as many stored samples as fit in `samples` with a single burst read, returning
+    /// how many were read.  Samples are in g, oldest first.
+    pub fn read_fifo(&mut self, samples: &mut [(f32, f32, f32)]) -> Result<usize, I2C::Error> {
+        let count = usize::from(self.fifo_status()?.samples).min(samples.len());
+        if count == 0 {
+            return Ok(0);
+        }
+        let mut data: [u8; FIFO_SIZE * 6] = [0; FIFO_SIZE * 6];
+        self.i2c
+            .write_read(self.address, &[0x80 | OUT_X_L], &mut data[..count * 6])?;
+        for (sample, bytes) in samples.iter_mut().zip(data[..count * 6].chunks_exact(6)) {
+            *sample = (
+                self.scale(bytes[0], bytes[1]),
+                self.scale(bytes[2], bytes[3]),
+                self.scale(bytes[4], bytes[5]),
+            );
+        }
+        Ok(count)
+    }
+
+    /// Acceleration along each axis in m/s².
+    pub fn metres_per_second_squared(&mut self) -> Result<(f32, f32, f32), I2C::Error> {
+        let (x, y, z) = self.acceleration()?;
+        Ok((
+            x * STANDARD_GRAVITY,
+            y * STANDARD_GRAVITY,
+            z * STANDARD_GRAVITY,
         ))
     }
 
@@ -178,7 +398,10 @@ impl<I2C: I2c> P26<I2C> {
                     .write_read(self.address, &[REG_CONTROL3 | 0x80], &mut data)?;
                 self.i2c
                     .write(self.address, &[REG_CONTROL3, data[0] | 0x80])?;
-                self.i2c.write(self.address, &[REG_CONTROL5, 0x08])?;
+                self.i2c
+                    .write_read(self.address, &[0x80 | REG_CONTROL5], &mut data)?;
+                self.i2c
+                    .write(self.address, &[REG_CONTROL5, data[0] | CONTROL5_LATCH_INT1])?;
                 self.i2c.write(
                     self.address,
                     &[
@@ -217,7 +440,6 @@ impl<I2C: I2c> P26<I2C> {
 #[cfg(all(test, not(all(target_arch = "arm", target_os = "none"))))]
 mod test {
     use crate::Driver;
-    use fixed::types::I2F14;
     extern crate std;
     use std::vec;
     extern crate embedded_hal;
@@ -225,7 +447,16 @@ mod test {
 
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::p26::{Error, Gravity, TapDetection, P26};
+    use crate::p26::{Error, FifoMode, FifoStatus, Gravity, Rate, Resolution, TapDetection, P26};
+
+    const fn p26(i2c: I2cMock) -> P26<I2cMock> {
+        P26 {
+            i2c,
+            address: 0x19,
+            range: Gravity::EarthTimes2,
+            resolution: Resolution::HighResolution,
+        }
+    }
 
     #[test]
     pub fn new() {
@@ -254,7 +485,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.set_range(Gravity::EarthTimes2), Ok(()));
         i2c_clone.done();
@@ -269,21 +500,62 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
-        assert_eq!(p26.set_rate(400), Ok(()));
+        assert_eq!(p26.set_rate(Rate::Hz400), Ok(()));
         i2c_clone.done();
     }
 
     #[test]
-    pub fn set_rate_error() {
-        let expectations = [];
+    pub fn set_resolution() {
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xA0], vec![0x77]),
+            I2cTransaction::write(0x19, vec![0x20, 0x7F]),
+            I2cTransaction::write_read(0x19, vec![0xA3], vec![0x88]),
+            I2cTransaction::write(0x19, vec![0x23, 0x80]),
+            I2cTransaction::write_read(0x19, vec![0xA0], vec![0x7F]),
+            I2cTransaction::write(0x19, vec![0x20, 0x77]),
+            I2cTransaction::write_read(0x19, vec![0xA3], vec![0x80]),
+            I2cTransaction::write(0x19, vec![0x23, 0x80]),
+        ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
-        assert_eq!(p26.set_rate(401), Err(Error::ArgumentError));
+        assert_eq!(p26.set_resolution(Resolution::LowPower), Ok(()));
+        assert_eq!(p26.set_resolution(Resolution::Normal), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn acceleration_scaling() {
+        let reading =
+            I2cTransaction::write_read(0x19, vec![0xA8], vec![0x00, 0x40, 0x00, 0xC0, 0x00, 0x00]);
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xA3], vec![0x88]),
+            I2cTransaction::write(0x19, vec![0x23, 0xB8]),
+            reading.clone(),
+            I2cTransaction::write_read(0x19, vec![0xA0], vec![0x77]),
+            I2cTransaction::write(0x19, vec![0x20, 0x7F]),
+            I2cTransaction::write_read(0x19, vec![0xA3], vec![0xB8]),
+            I2cTransaction::write(0x19, vec![0x23, 0xB0]),
+            reading,
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p26 = p26(i2c);
+
+        assert_eq!(p26.set_range(Gravity::EarthTimes16), Ok(()));
+        let (x, y, z) = p26.acceleration().unwrap();
+        assert!((x - 12.288).abs() < 1e-4);
+        assert!((y + 12.288).abs() < 1e-4);
+        assert!(z.abs() < 1e-4);
+
+        assert_eq!(p26.set_resolution(Resolution::LowPower), Ok(()));
+        let (x_ms2, _, _) = p26.metres_per_second_squared().unwrap();
+        assert!((x_ms2 - 12.288 * 9.806_65).abs() < 1e-3);
         i2c_clone.done();
     }
 
@@ -293,7 +565,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.data_ready(), Ok(true));
         i2c_clone.done();
@@ -305,7 +577,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.data_ready(), Ok(false));
         i2c_clone.done();
@@ -321,16 +593,104 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
+
+        let (x, y, z) = p26.acceleration().unwrap();
+        assert!((x - 0.048).abs() < 1e-6);
+        assert!((y + 0.009).abs() < 1e-6);
+        assert!((z - 1.04).abs() < 1e-6);
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_fifo() {
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x08]),
+            I2cTransaction::write(0x19, vec![0x24, 0x48]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x00]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x90]),
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x48]),
+            I2cTransaction::write(0x19, vec![0x24, 0x08]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x00]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x00]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p26 = p26(i2c);
+
+        assert_eq!(p26.set_fifo(FifoMode::Stream, 16), Ok(()));
+        assert_eq!(p26.set_fifo(FifoMode::Bypass, 0), Ok(()));
+        assert_eq!(p26.set_fifo(FifoMode::Fifo, 32), Err(Error::ArgumentError));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn fifo_status() {
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0x91]),
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0xDF]),
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0x20]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p26 = p26(i2c);
 
         assert_eq!(
-            p26.acceleration(),
-            Ok((
-                I2F14::lit("0.0469"),
-                I2F14::lit("-0.0088"),
-                I2F14::lit("1.0156")
-            ))
+            p26.fifo_status(),
+            Ok(FifoStatus {
+                watermark: true,
+                overrun: false,
+                samples: 17,
+            })
         );
+        assert_eq!(
+            p26.fifo_status(),
+            Ok(FifoStatus {
+                watermark: true,
+                overrun: true,
+                samples: 32,
+            })
+        );
+        assert_eq!(
+            p26.fifo_status(),
+            Ok(FifoStatus {
+                watermark: false,
+                overrun: false,
+                samples: 0,
+            })
+        );
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn read_fifo() {
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0x02]),
+            I2cTransaction::write_read(
+                0x19,
+                vec![0xA8],
+                vec![
+                    0x00, 0x40, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00,
+                ],
+            ),
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0x05]),
+            I2cTransaction::write_read(0x19, vec![0xA8], vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x40]),
+            I2cTransaction::write_read(0x19, vec![0xAF], vec![0x20]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p26 = p26(i2c);
+
+        let mut samples = [(0.0, 0.0, 0.0); 4];
+        assert_eq!(p26.read_fifo(&mut samples), Ok(2));
+        assert_eq!(samples[0], (1.024, 0.0, -1.024));
+        assert_eq!(samples[1], (0.0, 0.512, 0.0));
+        assert_eq!(p26.read_fifo(&mut samples[..1]), Ok(1));
+        assert_eq!(samples[0], (0.0, 0.0, 1.024));
+        assert_eq!(p26.read_fifo(&mut samples), Ok(0));
         i2c_clone.done();
     }
 
@@ -367,7 +727,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.set_tap(TapDetection::Disabled, 40, 10, 80, 255), Ok(()));
         i2c_clone.done();
@@ -379,7 +739,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(
             p26.set_tap(TapDetection::Disabled, 128, 10, 80, 255),
@@ -393,15 +753,40 @@ mod test {
         let expectations = [
             I2cTransaction::write_read(0x19, vec![0xA2], vec![0x0]),
             I2cTransaction::write(0x19, vec![0x22, 0x80]),
-            I2cTransaction::write(0x19, vec![0x25, 0x08]),
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x00]),
+            I2cTransaction::write(0x19, vec![0x24, 0x08]),
+            I2cTransaction::write(0x19, vec![0x38, 0x15]),
+            I2cTransaction::write(0x19, vec![0xBA, 0xFF, 10, 80, 255]),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let mut i2c_clone = i2c.clone();
+
+        let mut p26 = p26(i2c);
+
+        assert_eq!(p26.set_tap(TapDetection::Single, 127, 10, 80, 255), Ok(()));
+        i2c_clone.done();
+    }
+
+    #[test]
+    pub fn set_tap_keeps_fifo() {
+        let expectations = [
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x00]),
+            I2cTransaction::write(0x19, vec![0x24, 0x40]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x00]),
+            I2cTransaction::write(0x19, vec![0x2E, 0x90]),
+            I2cTransaction::write_read(0x19, vec![0xA2], vec![0x0]),
+            I2cTransaction::write(0x19, vec![0x22, 0x80]),
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x40]),
+            I2cTransaction::write(0x19, vec![0x24, 0x48]),
             I2cTransaction::write(0x19, vec![0x38, 0x15]),
             I2cTransaction::write(0x19, vec![0xBA, 0xFF, 10, 80, 255]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
+        assert_eq!(p26.set_fifo(FifoMode::Stream, 16), Ok(()));
         assert_eq!(p26.set_tap(TapDetection::Single, 127, 10, 80, 255), Ok(()));
         i2c_clone.done();
     }
@@ -411,14 +796,15 @@ mod test {
         let expectations = [
             I2cTransaction::write_read(0x19, vec![0xA2], vec![0x0]),
             I2cTransaction::write(0x19, vec![0x22, 0x80]),
-            I2cTransaction::write(0x19, vec![0x25, 0x08]),
+            I2cTransaction::write_read(0x19, vec![0xA4], vec![0x00]),
+            I2cTransaction::write(0x19, vec![0x24, 0x08]),
             I2cTransaction::write(0x19, vec![0x38, 0x2A]),
             I2cTransaction::write(0x19, vec![0xBA, 0xFF, 15, 60, 200]),
         ];
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.set_tap(TapDetection::Double, 127, 15, 60, 200), Ok(()));
         i2c_clone.done();
@@ -434,7 +820,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.tapped(), Ok(false));
         i2c_clone.done();
@@ -449,7 +835,7 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = p26(i2c);
 
         assert_eq!(p26.tapped(), Ok(true));
         i2c_clone.done();
@@ -23,7 +23,8 @@ mod test {
 
     use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
 
-    use crate::{p26::P26, WhoAmI};
+    use crate::p26::{Gravity, Resolution, P26};
+    use crate::WhoAmI;
 
     #[test]
     pub fn whoami() {
@@ -31,7 +32,12 @@ mod test {
         let i2c = I2cMock::new(&expectations);
         let mut i2c_clone = i2c.clone();
 
-        let mut p26 = P26 { i2c, address: 0x19 };
+        let mut p26 = P26 {
+            i2c,
+            address: 0x19,
+            range: Gravity::EarthTimes2,
+            resolution: Resolution::Normal,
+        };
 
         assert_eq!(p26.whoami(), Ok(0x33));
         i2c_clone.done();